@@ -0,0 +1,480 @@
+// Maximum weight matching in general graphs, Edmonds' blossom algorithm
+// with the primal-dual method of Galil, O(n^3).
+// Integer weights keep every dual variable integral, so no tolerance is needed.
+
+struct Blossom<'a> {
+    nvertex:           usize,
+    edges:             &'a [(usize, usize, i64)],
+    endpoint:          Vec<usize>,
+    neighbend:         Vec<Vec<usize>>,
+    mate:              Vec<Option<usize>>,
+    label:             Vec<u8>,
+    labelend:          Vec<Option<usize>>,
+    inblossom:         Vec<usize>,
+    blossomparent:     Vec<Option<usize>>,
+    blossomchilds:     Vec<Vec<usize>>,
+    blossombase:       Vec<Option<usize>>,
+    blossomendps:      Vec<Vec<usize>>,
+    bestedge:          Vec<Option<usize>>,
+    blossombestedges:  Vec<Option<Vec<usize>>>,
+    unusedblossoms:    Vec<usize>,
+    dualvar:           Vec<i64>,
+    allowedge:         Vec<bool>,
+    queue:             Vec<usize>,
+}
+
+// python style index into a cyclic list
+fn at(v: &[usize], j: isize) -> usize {
+    let len = v.len() as isize;
+    v[(((j % len) + len) % len) as usize]
+}
+
+impl<'a> Blossom<'a> {
+    fn slack(&self, k: usize) -> i64 {
+        let (i, j, wt) = self.edges[k];
+        self.dualvar[i] + self.dualvar[j] - 2 * wt
+    }
+
+    fn leaves(&self, b: usize) -> Vec<usize> {
+        let mut ret = Vec::new();
+        let mut stack = vec![b];
+        while let Some(t) = stack.pop() {
+            if t < self.nvertex { ret.push(t); }
+            else { stack.extend(self.blossomchilds[t].iter().copied()); }
+        }
+        ret
+    }
+
+    fn assign_label(&mut self, w: usize, t: u8, p: Option<usize>) {
+        let b = self.inblossom[w];
+        self.label[w] = t;
+        self.label[b] = t;
+        self.labelend[w] = p;
+        self.labelend[b] = p;
+        self.bestedge[w] = None;
+        self.bestedge[b] = None;
+        if t == 1 {
+            let leaves = self.leaves(b);
+            self.queue.extend(leaves);
+        } else if t == 2 {
+            let base = self.blossombase[b].unwrap();
+            let mb = self.mate[base].unwrap();
+            self.assign_label(self.endpoint[mb], 1, Some(mb ^ 1));
+        }
+    }
+
+    fn scan_blossom(&mut self, v: usize, w: usize) -> Option<usize> {
+        let mut path = Vec::new();
+        let mut base = None;
+        let (mut v, mut w) = (Some(v), Some(w));
+
+        while v.is_some() || w.is_some() {
+            let mut b = self.inblossom[v.unwrap()];
+            if self.label[b] & 4 != 0 {
+                base = self.blossombase[b];
+                break;
+            }
+            path.push(b);
+            self.label[b] = 5;
+            match self.labelend[b] {
+                None => { v = None; }
+                Some(le) => {
+                    let t = self.endpoint[le];
+                    b = self.inblossom[t];
+                    v = Some(self.endpoint[self.labelend[b].unwrap()]);
+                }
+            }
+            if w.is_some() { std::mem::swap(&mut v, &mut w); }
+        }
+
+        for b in path { self.label[b] = 1; }
+        base
+    }
+
+    fn add_blossom(&mut self, base: usize, k: usize) {
+        let (mut v, mut w, _) = self.edges[k];
+        let bb = self.inblossom[base];
+        let mut bv = self.inblossom[v];
+        let mut bw = self.inblossom[w];
+        let b = self.unusedblossoms.pop().unwrap();
+
+        self.blossombase[b] = Some(base);
+        self.blossomparent[b] = None;
+        self.blossomparent[bb] = Some(b);
+
+        let mut path = Vec::new();
+        let mut endps = Vec::new();
+        while bv != bb {
+            self.blossomparent[bv] = Some(b);
+            path.push(bv);
+            endps.push(self.labelend[bv].unwrap());
+            v = self.endpoint[self.labelend[bv].unwrap()];
+            bv = self.inblossom[v];
+        }
+        path.push(bb);
+        path.reverse();
+        endps.reverse();
+        endps.push(2 * k);
+        while bw != bb {
+            self.blossomparent[bw] = Some(b);
+            path.push(bw);
+            endps.push(self.labelend[bw].unwrap() ^ 1);
+            w = self.endpoint[self.labelend[bw].unwrap()];
+            bw = self.inblossom[w];
+        }
+
+        self.label[b] = 1;
+        self.labelend[b] = self.labelend[bb];
+        self.dualvar[b] = 0;
+        self.blossomchilds[b] = path.clone();
+        self.blossomendps[b] = endps;
+
+        for v in self.leaves(b) {
+            if self.label[self.inblossom[v]] == 2 { self.queue.push(v); }
+            self.inblossom[v] = b;
+        }
+
+        let mut bestedgeto: Vec<Option<usize>> = vec![None; 2 * self.nvertex];
+        for &bv in &path {
+            let nblists: Vec<Vec<usize>> = match self.blossombestedges[bv].take() {
+                None => self.leaves(bv).into_iter()
+                    .map(|v| self.neighbend[v].iter().map(|p| p / 2).collect())
+                    .collect(),
+                Some(list) => vec![list]
+            };
+            for nblist in nblists {
+                for k in nblist {
+                    let (mut i, mut j, _) = self.edges[k];
+                    if self.inblossom[j] == b { std::mem::swap(&mut i, &mut j); }
+                    let bj = self.inblossom[j];
+                    if bj != b && self.label[bj] == 1
+                        && bestedgeto[bj].is_none_or(|e| self.slack(k) < self.slack(e))
+                    {
+                        bestedgeto[bj] = Some(k);
+                    }
+                }
+            }
+            self.bestedge[bv] = None;
+        }
+
+        let list: Vec<usize> = bestedgeto.into_iter().flatten().collect();
+        self.bestedge[b] = None;
+        for &k in &list {
+            if self.bestedge[b].is_none_or(|e| self.slack(k) < self.slack(e)) {
+                self.bestedge[b] = Some(k);
+            }
+        }
+        self.blossombestedges[b] = Some(list);
+    }
+
+    fn expand_blossom(&mut self, b: usize, endstage: bool) {
+        for s in self.blossomchilds[b].clone() {
+            self.blossomparent[s] = None;
+            if s < self.nvertex {
+                self.inblossom[s] = s;
+            } else if endstage && self.dualvar[s] == 0 {
+                self.expand_blossom(s, endstage);
+            } else {
+                for v in self.leaves(s) { self.inblossom[v] = s; }
+            }
+        }
+
+        if !endstage && self.label[b] == 2 {
+            let childs = self.blossomchilds[b].clone();
+            let endps = self.blossomendps[b].clone();
+            let entrychild = self.inblossom[self.endpoint[self.labelend[b].unwrap() ^ 1]];
+            let mut j = childs.iter().position(|&c| c == entrychild).unwrap() as isize;
+            let (jstep, endptrick): (isize, usize) = if j & 1 == 1 {
+                j -= childs.len() as isize;
+                (1, 0)
+            } else {
+                (-1, 1)
+            };
+
+            let mut p = self.labelend[b].unwrap();
+            while j != 0 {
+                let t = self.endpoint[p ^ 1];
+                self.label[t] = 0;
+                let q = at(&endps, j - endptrick as isize) ^ endptrick ^ 1;
+                let t2 = self.endpoint[q];
+                self.label[t2] = 0;
+                self.assign_label(self.endpoint[p ^ 1], 2, Some(p));
+                self.allowedge[at(&endps, j - endptrick as isize) / 2] = true;
+                j += jstep;
+                p = at(&endps, j - endptrick as isize) ^ endptrick;
+                self.allowedge[p / 2] = true;
+                j += jstep;
+            }
+
+            let bv = at(&childs, j);
+            let t = self.endpoint[p ^ 1];
+            self.label[t] = 2;
+            self.label[bv] = 2;
+            self.labelend[t] = Some(p);
+            self.labelend[bv] = Some(p);
+            self.bestedge[bv] = None;
+            j += jstep;
+
+            while at(&childs, j) != entrychild {
+                let bv = at(&childs, j);
+                if self.label[bv] == 1 { j += jstep; continue; }
+                if let Some(v) = self.leaves(bv).into_iter().find(|&v| self.label[v] != 0) {
+                    self.label[v] = 0;
+                    let m = self.mate[self.blossombase[bv].unwrap()].unwrap();
+                    let t = self.endpoint[m];
+                    self.label[t] = 0;
+                    self.assign_label(v, 2, self.labelend[v]);
+                }
+                j += jstep;
+            }
+        }
+
+        self.label[b] = 0;
+        self.labelend[b] = None;
+        self.blossomchilds[b].clear();
+        self.blossomendps[b].clear();
+        self.blossombase[b] = None;
+        self.blossombestedges[b] = None;
+        self.bestedge[b] = None;
+        self.unusedblossoms.push(b);
+    }
+
+    fn augment_blossom(&mut self, b: usize, v: usize) {
+        let mut t = v;
+        while self.blossomparent[t] != Some(b) { t = self.blossomparent[t].unwrap(); }
+        if t >= self.nvertex { self.augment_blossom(t, v); }
+
+        let i = self.blossomchilds[b].iter().position(|&c| c == t).unwrap();
+        let mut j = i as isize;
+        let (jstep, endptrick): (isize, usize) = if i & 1 == 1 {
+            j -= self.blossomchilds[b].len() as isize;
+            (1, 0)
+        } else {
+            (-1, 1)
+        };
+
+        while j != 0 {
+            j += jstep;
+            let t = at(&self.blossomchilds[b], j);
+            let p = at(&self.blossomendps[b], j - endptrick as isize) ^ endptrick;
+            if t >= self.nvertex { self.augment_blossom(t, self.endpoint[p]); }
+            j += jstep;
+            let t = at(&self.blossomchilds[b], j);
+            if t >= self.nvertex { self.augment_blossom(t, self.endpoint[p ^ 1]); }
+            let (a, c) = (self.endpoint[p], self.endpoint[p ^ 1]);
+            self.mate[a] = Some(p ^ 1);
+            self.mate[c] = Some(p);
+        }
+
+        self.blossomchilds[b].rotate_left(i);
+        self.blossomendps[b].rotate_left(i);
+        self.blossombase[b] = self.blossombase[self.blossomchilds[b][0]];
+    }
+
+    fn augment_matching(&mut self, k: usize) {
+        let (v, w, _) = self.edges[k];
+        for (s, p) in [(v, 2 * k + 1), (w, 2 * k)] {
+            let (mut s, mut p) = (s, p);
+            loop {
+                let bs = self.inblossom[s];
+                if bs >= self.nvertex { self.augment_blossom(bs, s); }
+                self.mate[s] = Some(p);
+                let le = match self.labelend[bs] {
+                    None => { break; }
+                    Some(le) => le
+                };
+                let t = self.endpoint[le];
+                let bt = self.inblossom[t];
+                let lt = self.labelend[bt].unwrap();
+                s = self.endpoint[lt];
+                let j = self.endpoint[lt ^ 1];
+                if bt >= self.nvertex { self.augment_blossom(bt, j); }
+                self.mate[j] = Some(lt);
+                p = lt ^ 1;
+            }
+        }
+    }
+
+    fn solve(&mut self) {
+        let n = self.nvertex;
+
+        for _ in 0..n {
+            self.label.iter_mut().for_each(|x| *x = 0);
+            self.bestedge.iter_mut().for_each(|x| *x = None);
+            for b in n..2 * n { self.blossombestedges[b] = None; }
+            self.allowedge.iter_mut().for_each(|x| *x = false);
+            self.queue.clear();
+
+            for v in 0..n {
+                if self.mate[v].is_none() && self.label[self.inblossom[v]] == 0 {
+                    self.assign_label(v, 1, None);
+                }
+            }
+
+            let mut augmented = false;
+            loop {
+                while !augmented {
+                    let v = match self.queue.pop() { None => { break; } Some(v) => v };
+
+                    for p in self.neighbend[v].clone() {
+                        let k = p / 2;
+                        let w = self.endpoint[p];
+                        if self.inblossom[v] == self.inblossom[w] { continue; }
+
+                        let mut kslack = 0;
+                        if !self.allowedge[k] {
+                            kslack = self.slack(k);
+                            if kslack <= 0 { self.allowedge[k] = true; }
+                        }
+
+                        if self.allowedge[k] {
+                            if self.label[self.inblossom[w]] == 0 {
+                                self.assign_label(w, 2, Some(p ^ 1));
+                            } else if self.label[self.inblossom[w]] == 1 {
+                                match self.scan_blossom(v, w) {
+                                    Some(base) => { self.add_blossom(base, k); }
+                                    None => {
+                                        self.augment_matching(k);
+                                        augmented = true;
+                                        break;
+                                    }
+                                }
+                            } else if self.label[w] == 0 {
+                                self.label[w] = 2;
+                                self.labelend[w] = Some(p ^ 1);
+                            }
+                        } else if self.label[self.inblossom[w]] == 1 {
+                            let b = self.inblossom[v];
+                            if self.bestedge[b].is_none_or(|e| kslack < self.slack(e)) {
+                                self.bestedge[b] = Some(k);
+                            }
+                        } else if self.label[w] == 0
+                            && self.bestedge[w].is_none_or(|e| kslack < self.slack(e))
+                        {
+                            self.bestedge[w] = Some(k);
+                        }
+                    }
+                }
+
+                if augmented { break; }
+
+                // 1: stop, 2: grow, 3: new blossom, 4: expand blossom
+                let mut deltatype = 1;
+                let mut delta = *self.dualvar[..n].iter().min().unwrap();
+                let mut deltaedge = None;
+                let mut deltablossom = None;
+
+                for v in 0..n {
+                    if self.label[self.inblossom[v]] == 0 {
+                        if let Some(e) = self.bestedge[v] {
+                            let d = self.slack(e);
+                            if d < delta { delta = d; deltatype = 2; deltaedge = Some(e); }
+                        }
+                    }
+                }
+
+                for b in 0..2 * n {
+                    if self.blossomparent[b].is_none() && self.label[b] == 1 {
+                        if let Some(e) = self.bestedge[b] {
+                            let d = self.slack(e) / 2;
+                            if d < delta { delta = d; deltatype = 3; deltaedge = Some(e); }
+                        }
+                    }
+                }
+
+                for b in n..2 * n {
+                    if self.blossombase[b].is_some() && self.blossomparent[b].is_none()
+                        && self.label[b] == 2 && self.dualvar[b] < delta
+                    {
+                        delta = self.dualvar[b];
+                        deltatype = 4;
+                        deltablossom = Some(b);
+                    }
+                }
+
+                for v in 0..n {
+                    match self.label[self.inblossom[v]] {
+                        1 => { self.dualvar[v] -= delta; }
+                        2 => { self.dualvar[v] += delta; }
+                        _ => {}
+                    }
+                }
+                for b in n..2 * n {
+                    if self.blossombase[b].is_some() && self.blossomparent[b].is_none() {
+                        match self.label[b] {
+                            1 => { self.dualvar[b] += delta; }
+                            2 => { self.dualvar[b] -= delta; }
+                            _ => {}
+                        }
+                    }
+                }
+
+                match deltatype {
+                    1 => { break; }
+                    2 => {
+                        let e = deltaedge.unwrap();
+                        self.allowedge[e] = true;
+                        let (mut i, j, _) = self.edges[e];
+                        if self.label[self.inblossom[i]] == 0 { i = j; }
+                        self.queue.push(i);
+                    }
+                    3 => {
+                        let e = deltaedge.unwrap();
+                        self.allowedge[e] = true;
+                        self.queue.push(self.edges[e].0);
+                    }
+                    _ => { self.expand_blossom(deltablossom.unwrap(), false); }
+                }
+            }
+
+            if !augmented { break; }
+
+            for b in n..2 * n {
+                if self.blossomparent[b].is_none() && self.blossombase[b].is_some()
+                    && self.label[b] == 1 && self.dualvar[b] == 0
+                {
+                    self.expand_blossom(b, true);
+                }
+            }
+        }
+    }
+}
+
+// mate of every vertex in a maximum weight matching of the graph on
+// vertices 0..n, edges are (u, v, weight) with u != v
+pub fn max_weight_matching(n: usize, edges: &[(usize, usize, i64)]) -> Vec<Option<usize>> {
+    if edges.is_empty() { return vec![None; n]; }
+
+    let maxweight = edges.iter().map(|e| e.2).max().unwrap().max(0);
+
+    let mut neighbend = vec![Vec::new(); n];
+    for (k, &(i, j, _)) in edges.iter().enumerate() {
+        neighbend[i].push(2 * k + 1);
+        neighbend[j].push(2 * k);
+    }
+
+    let mut blossom = Blossom {
+        nvertex: n,
+        edges,
+        endpoint: (0..2 * edges.len()).map(|p| if p % 2 == 0 { edges[p / 2].0 } else { edges[p / 2].1 }).collect(),
+        neighbend,
+        mate: vec![None; n],
+        label: vec![0; 2 * n],
+        labelend: vec![None; 2 * n],
+        inblossom: (0..n).collect(),
+        blossomparent: vec![None; 2 * n],
+        blossomchilds: vec![Vec::new(); 2 * n],
+        blossombase: (0..2 * n).map(|b| if b < n { Some(b) } else { None }).collect(),
+        blossomendps: vec![Vec::new(); 2 * n],
+        bestedge: vec![None; 2 * n],
+        blossombestedges: vec![None; 2 * n],
+        unusedblossoms: (n..2 * n).collect(),
+        dualvar: (0..2 * n).map(|b| if b < n { maxweight } else { 0 }).collect(),
+        allowedge: vec![false; edges.len()],
+        queue: Vec::new(),
+    };
+
+    blossom.solve();
+
+    blossom.mate.iter().map(|m| m.map(|p| blossom.endpoint[p])).collect()
+}
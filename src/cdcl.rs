@@ -0,0 +1,330 @@
+use crate::sat::SAT;
+use std::collections::BinaryHeap;
+use std::time::{Duration, Instant};
+
+// Conflict driven clause learning on the bad-event semantics of `SAT`:
+// a clause is violated when all of its literals are true, so the solver looks
+// for an assignment in which every clause has at least one false literal.
+// Internally every clause is stored negated as an ordinary disjunction.
+
+#[derive(Debug)]
+pub enum SolveResult {
+    Satisfiable(Vec<bool>),
+    Unsatisfiable,
+    Unknown
+}
+
+impl std::fmt::Display for SolveResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Satisfiable(_) => { write!(f, "SAT") }
+            Self::Unsatisfiable => { write!(f, "UNSAT") }
+            Self::Unknown => { write!(f, "UNKNOWN") }
+        }
+    }
+}
+
+pub struct Budget {
+    pub conflicts: usize,
+    pub time: Duration
+}
+
+// literal 2 * v is "v is true", 2 * v + 1 is "v is false"
+type Lit = usize;
+
+fn neg(l: Lit) -> Lit { l ^ 1 }
+
+struct Solver {
+    clause:   Vec<Vec<Lit>>,
+    watch:    Vec<Vec<usize>>,
+    value:    Vec<Option<bool>>,
+    level:    Vec<usize>,
+    reason:   Vec<Option<usize>>,
+    trail:    Vec<Lit>,
+    trail_lim: Vec<usize>,
+    qhead:    usize,
+    activity: Vec<f64>,
+    inc:      f64,
+    heap:     BinaryHeap<(u64, usize)>,
+    phase:    Vec<bool>,
+    seen:     Vec<bool>,
+}
+
+fn luby(i: usize) -> usize {
+    // i-th element (from 0) of 1 1 2 1 1 2 4 1 1 2 ...
+    let (mut size, mut seq) = (1, 0);
+    while size < i + 1 { seq += 1; size = 2 * size + 1; }
+    let mut x = i;
+    while size - 1 != x {
+        size = (size - 1) / 2;
+        seq -= 1;
+        x %= size;
+    }
+    1 << seq
+}
+
+impl Solver {
+    fn new(n: usize) -> Self {
+        Self {
+            clause: Vec::new(),
+            watch: crate::new_vector(2 * n, Vec::new()),
+            value: crate::new_vector(n, None),
+            level: crate::new_vector(n, 0),
+            reason: crate::new_vector(n, None),
+            trail: Vec::new(),
+            trail_lim: Vec::new(),
+            qhead: 0,
+            activity: crate::new_vector(n, 0.0),
+            inc: 1.0,
+            heap: (0..n).map(|v| (0f64.to_bits(), v)).collect(),
+            phase: crate::new_vector(n, false),
+            seen: crate::new_vector(n, false),
+        }
+    }
+
+    fn lit_value(&self, l: Lit) -> Option<bool> {
+        self.value[l / 2].map(|b| b ^ (l & 1 == 1))
+    }
+
+    fn decision_level(&self) -> usize {
+        self.trail_lim.len()
+    }
+
+    fn enqueue(&mut self, l: Lit, from: Option<usize>) {
+        let v = l / 2;
+        self.value[v] = Some(l & 1 == 0);
+        self.level[v] = self.decision_level();
+        self.reason[v] = from;
+        self.trail.push(l);
+    }
+
+    fn attach(&mut self, c: Vec<Lit>) -> usize {
+        let id = self.clause.len();
+        self.watch[neg(c[0])].push(id);
+        self.watch[neg(c[1])].push(id);
+        self.clause.push(c);
+        id
+    }
+
+    // returns the conflicting clause, if any
+    fn propagate(&mut self) -> Option<usize> {
+        while self.qhead < self.trail.len() {
+            // clauses watching the literal that just became false
+            let p = self.trail[self.qhead];
+            self.qhead += 1;
+
+            let ws = std::mem::take(&mut self.watch[p]);
+            let mut keep = Vec::with_capacity(ws.len());
+            let mut conflict = None;
+
+            for (i, &id) in ws.iter().enumerate() {
+                if conflict.is_some() { keep.extend_from_slice(&ws[i..]); break; }
+
+                let false_lit = neg(p);
+                if self.clause[id][0] == false_lit { self.clause[id].swap(0, 1); }
+
+                let first = self.clause[id][0];
+                if self.lit_value(first) == Some(true) { keep.push(id); continue; }
+
+                let len = self.clause[id].len();
+                let mut moved = false;
+                for k in 2..len {
+                    let l = self.clause[id][k];
+                    if self.lit_value(l) != Some(false) {
+                        self.clause[id].swap(1, k);
+                        self.watch[neg(l)].push(id);
+                        moved = true;
+                        break;
+                    }
+                }
+                if moved { continue; }
+
+                keep.push(id);
+                match self.lit_value(first) {
+                    Some(false) => { conflict = Some(id); }
+                    _ => { self.enqueue(first, Some(id)); }
+                }
+            }
+
+            self.watch[p] = keep;
+            if conflict.is_some() { return conflict; }
+        }
+        None
+    }
+
+    fn bump(&mut self, v: usize) {
+        self.activity[v] += self.inc;
+        if self.activity[v] > 1e100 {
+            for a in self.activity.iter_mut() { *a *= 1e-100; }
+            self.inc *= 1e-100;
+            self.rebuild_heap();
+        }
+        self.heap.push((self.activity[v].to_bits(), v));
+        // every bump leaves a stale entry behind
+        if self.heap.len() > 4 * self.value.len() { self.rebuild_heap(); }
+    }
+
+    // one entry with the current activity for every unassigned variable
+    fn rebuild_heap(&mut self) {
+        self.heap = (0..self.value.len())
+            .filter(|&v| self.value[v].is_none())
+            .map(|v| (self.activity[v].to_bits(), v))
+            .collect();
+    }
+
+    // first UIP clause and the level to jump back to
+    fn analyze(&mut self, conflict: usize) -> (Vec<Lit>, usize) {
+        let mut learnt = vec![0];
+        let mut counter = 0;
+        let mut index = self.trail.len();
+        let mut id = conflict;
+        let mut p = None;
+
+        loop {
+            let start = if p.is_none() { 0 } else { 1 };
+            for k in start..self.clause[id].len() {
+                let q = self.clause[id][k];
+                let v = q / 2;
+                if !self.seen[v] && self.level[v] > 0 {
+                    self.seen[v] = true;
+                    self.bump(v);
+                    if self.level[v] == self.decision_level() { counter += 1; }
+                    else { learnt.push(q); }
+                }
+            }
+
+            loop {
+                index -= 1;
+                if self.seen[self.trail[index] / 2] { break; }
+            }
+            let l = self.trail[index];
+            self.seen[l / 2] = false;
+            p = Some(l);
+            counter -= 1;
+            if counter == 0 { break; }
+            id = self.reason[l / 2].unwrap();
+        }
+
+        learnt[0] = neg(p.unwrap());
+        for &q in &learnt[1..] { self.seen[q / 2] = false; }
+        self.inc /= 0.95;
+
+        let mut back = 0;
+        if learnt.len() > 1 {
+            let k = (1..learnt.len()).max_by_key(|&k| self.level[learnt[k] / 2]).unwrap();
+            learnt.swap(1, k);
+            back = self.level[learnt[1] / 2];
+        }
+
+        (learnt, back)
+    }
+
+    fn cancel_until(&mut self, lvl: usize) {
+        if self.decision_level() <= lvl { return; }
+        let lim = self.trail_lim[lvl];
+        for k in (lim..self.trail.len()).rev() {
+            let v = self.trail[k] / 2;
+            self.phase[v] = self.trail[k] & 1 == 0;
+            self.value[v] = None;
+            self.reason[v] = None;
+            self.heap.push((self.activity[v].to_bits(), v));
+        }
+        self.trail.truncate(lim);
+        self.trail_lim.truncate(lvl);
+        self.qhead = lim;
+    }
+
+    fn pick_branch(&mut self) -> Option<Lit> {
+        while let Some((_, v)) = self.heap.pop() {
+            if self.value[v].is_none() {
+                return Some(2 * v + if self.phase[v] { 0 } else { 1 });
+            }
+        }
+        None
+    }
+
+    fn solve(&mut self, budget: &Budget) -> SolveResult {
+        let start = Instant::now();
+        let mut conflicts = 0;
+        let mut restart = 0;
+
+        loop {
+            let limit = 100 * luby(restart);
+            let mut local = 0;
+
+            loop {
+                match self.propagate() {
+                    Some(conflict) => {
+                        conflicts += 1;
+                        local += 1;
+                        if self.decision_level() == 0 { return SolveResult::Unsatisfiable; }
+
+                        let (learnt, back) = self.analyze(conflict);
+                        self.cancel_until(back);
+                        if learnt.len() == 1 {
+                            self.enqueue(learnt[0], None);
+                        } else {
+                            let first = learnt[0];
+                            let id = self.attach(learnt);
+                            self.enqueue(first, Some(id));
+                        }
+
+                        if conflicts >= budget.conflicts || start.elapsed() >= budget.time {
+                            return SolveResult::Unknown;
+                        }
+                    }
+                    None => {
+                        if local >= limit { break; }
+                        match self.pick_branch() {
+                            None => {
+                                return SolveResult::Satisfiable(
+                                    self.value.iter().map(|b| b.unwrap()).collect()
+                                );
+                            }
+                            Some(l) => {
+                                self.trail_lim.push(self.trail.len());
+                                self.enqueue(l, None);
+                            }
+                        }
+                    }
+                }
+            }
+
+            self.cancel_until(0);
+            self.rebuild_heap();
+            restart += 1;
+        }
+    }
+}
+
+pub fn solve(sat: &SAT, budget: &Budget) -> SolveResult {
+    let n = sat.variable_count();
+    let mut solver = Solver::new(n);
+    let mut units = Vec::new();
+
+    for c in sat {
+        let mut lits: Vec<Lit> = c.into_iter()
+            .map(|&l| 2 * (l.unsigned_abs() - 1) + (l > 0) as usize)
+            .collect();
+        lits.sort();
+        lits.dedup();
+
+        if lits.windows(2).any(|w| w[0] / 2 == w[1] / 2) { continue; }
+
+        match lits.len() {
+            0 => { return SolveResult::Unsatisfiable; }
+            1 => { units.push(lits[0]); }
+            _ => { solver.attach(lits); }
+        }
+    }
+
+    for l in units {
+        match solver.lit_value(l) {
+            Some(false) => { return SolveResult::Unsatisfiable; }
+            Some(true) => {}
+            None => { solver.enqueue(l, None); }
+        }
+    }
+
+    solver.solve(budget)
+}
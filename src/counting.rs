@@ -0,0 +1,427 @@
+use crate::sat::SAT;
+use std::collections::{BTreeSet, HashMap};
+
+// exact non-negative integer, little endian 32-bit limbs
+#[derive(Clone, PartialEq, Eq)]
+pub struct BigCount {
+    limb: Vec<u32>
+}
+
+impl BigCount {
+    pub fn from_u64(x: u64) -> Self {
+        let mut ret = Self { limb: vec![x as u32, (x >> 32) as u32] };
+        ret.trim();
+        ret
+    }
+
+    pub fn pow2(k: usize) -> Self {
+        let mut limb = crate::new_vector(k / 32 + 1, 0u32);
+        limb[k / 32] = 1 << (k % 32);
+        Self { limb }
+    }
+
+    fn trim(&mut self) {
+        while self.limb.last() == Some(&0) { self.limb.pop(); }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limb.is_empty()
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        let len = self.limb.len().max(other.limb.len());
+        let mut limb = Vec::with_capacity(len + 1);
+        let mut carry = 0u64;
+
+        for i in 0..len {
+            let s = carry
+                + *self.limb.get(i).unwrap_or(&0) as u64
+                + *other.limb.get(i).unwrap_or(&0) as u64;
+            limb.push(s as u32);
+            carry = s >> 32;
+        }
+        limb.push(carry as u32);
+
+        let mut ret = Self { limb };
+        ret.trim();
+        ret
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        let mut limb = crate::new_vector(self.limb.len() + other.limb.len() + 1, 0u32);
+
+        for (i, &a) in self.limb.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.limb.iter().enumerate() {
+                let s = limb[i + j] as u64 + a as u64 * b as u64 + carry;
+                limb[i + j] = s as u32;
+                carry = s >> 32;
+            }
+            limb[i + other.limb.len()] = carry as u32;
+        }
+
+        let mut ret = Self { limb };
+        ret.trim();
+        ret
+    }
+
+    pub fn log2(&self) -> f64 {
+        // exact up to the top 64 bits
+        let len = self.limb.len();
+        match len {
+            0 => f64::NEG_INFINITY,
+            1 => (self.limb[0] as f64).log2(),
+            _ => {
+                let top = ((self.limb[len - 1] as u64) << 32 | self.limb[len - 2] as u64) as f64;
+                top.log2() + (32 * (len - 2)) as f64
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for BigCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() { return write!(f, "0"); }
+
+        let mut digits = Vec::new();
+        let mut rest = self.limb.clone();
+
+        while !rest.is_empty() {
+            let mut rem = 0u64;
+            for x in rest.iter_mut().rev() {
+                let cur = rem << 32 | *x as u64;
+                *x = (cur / 1000000000) as u32;
+                rem = cur % 1000000000;
+            }
+            digits.push(rem);
+            while rest.last() == Some(&0) { rest.pop(); }
+        }
+
+        write!(f, "{}", digits.pop().unwrap())?;
+        for d in digits.iter().rev() { write!(f, "{:09}", d)?; }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for BigCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+// DPLL model counter with unit propagation, connected components and
+// component caching. A clause is a bad event: it is violated when all of its
+// literals are true, so it is dropped as soon as one of its literals becomes
+// false and a clause with a single unassigned literal left forces it false.
+//
+// The same search gives the number of solutions and, with biased variables,
+// P(no clause is violated): a model is weighted by a product with one factor
+// per variable.
+trait Weight: Clone {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn is_zero(&self) -> bool;
+    fn plus(&self, other: &Self) -> Self;
+    fn times(&self, other: &Self) -> Self;
+    // a variable with bias q set to value
+    fn set(q: f64, value: bool) -> Self;
+    // k variables no clause depends on any more
+    fn free(k: usize) -> Self;
+    // what besides its clauses the weight of a component depends on
+    fn key(bias: &[f64]) -> Vec<u64>;
+}
+
+impl Weight for BigCount {
+    fn zero() -> Self { BigCount::from_u64(0) }
+    fn one() -> Self { BigCount::from_u64(1) }
+    fn is_zero(&self) -> bool { BigCount::is_zero(self) }
+    fn plus(&self, other: &Self) -> Self { self.add(other) }
+    fn times(&self, other: &Self) -> Self { self.mul(other) }
+    fn set(_: f64, _: bool) -> Self { BigCount::from_u64(1) }
+    fn free(k: usize) -> Self { BigCount::pow2(k) }
+    fn key(_: &[f64]) -> Vec<u64> { Vec::new() }
+}
+
+impl Weight for f64 {
+    fn zero() -> Self { 0.0 }
+    fn one() -> Self { 1.0 }
+    fn is_zero(&self) -> bool { *self == 0.0 }
+    fn plus(&self, other: &Self) -> Self { self + other }
+    fn times(&self, other: &Self) -> Self { self * other }
+    fn set(q: f64, value: bool) -> Self { if value { q } else { 1.0 - q } }
+    fn free(_: usize) -> Self { 1.0 }
+    fn key(bias: &[f64]) -> Vec<u64> { bias.iter().map(|q| q.to_bits()).collect() }
+}
+
+// candidates the branching looks ahead on
+const LOOKAHEAD: usize = 4;
+
+struct Counter<'a, W> {
+    // sorted literals, variables are 1-based
+    clause: Vec<Vec<isize>>,
+    occur: Vec<Vec<usize>>,
+    bias: &'a [f64],
+    value: Vec<Option<bool>>,
+    trail: Vec<usize>,
+    // literals of every clause set false and set true, a clause with a
+    // false literal is dropped
+    false_literal: Vec<usize>,
+    true_literal: Vec<usize>,
+    // unassigned literals over the clauses not dropped
+    residual: usize,
+    mark: Vec<usize>,
+    round: usize,
+    // a component with its variables renamed to 1, 2, ... in increasing
+    // order, as sorted and deduplicated clauses ending with 0
+    cache: HashMap<(Vec<isize>, Vec<u64>), W>,
+    // literals of the components the search may still branch on
+    budget: usize
+}
+
+impl<'a, W: Weight> Counter<'a, W> {
+    fn new(sat: &'a SAT, budget: usize) -> Self {
+        let m = sat.variable_count();
+        let mut clause = Vec::with_capacity(sat.size());
+
+        for c in sat {
+            let set: BTreeSet<isize> = c.into_iter().copied().collect();
+            // x and -x are never true together, such a clause is never violated
+            if set.iter().any(|l| set.contains(&-l)) { continue; }
+            clause.push(set.into_iter().collect::<Vec<_>>());
+        }
+
+        let mut occur = crate::new_vector(m + 1, Vec::new());
+        for (id, c) in clause.iter().enumerate() {
+            for l in c { occur[l.unsigned_abs()].push(id); }
+        }
+
+        let n = clause.len();
+        Self {
+            residual: clause.iter().map(|c| c.len()).sum(),
+            clause, occur,
+            bias: sat.bias(),
+            value: crate::new_vector(m + 1, None),
+            trail: Vec::new(),
+            false_literal: crate::new_vector(n, 0),
+            true_literal: crate::new_vector(n, 0),
+            mark: crate::new_vector(m + 1, 0),
+            round: 0,
+            cache: HashMap::new(),
+            budget
+        }
+    }
+
+    fn literal(&self, id: usize, v: usize) -> isize {
+        *self.clause[id].iter().find(|l| l.unsigned_abs() == v).unwrap()
+    }
+
+    // sets v and everything unit propagation forces on the trail, false on
+    // a violated clause
+    fn assign(&mut self, v: usize, value: bool) -> bool {
+        let mut queue = vec![(v, value)];
+
+        while let Some((v, value)) = queue.pop() {
+            match self.value[v] {
+                Some(x) if x == value => { continue; }
+                Some(_) => { return false; }
+                None => {}
+            }
+            self.value[v] = Some(value);
+            self.trail.push(v);
+
+            let mut violated = false;
+            for i in 0..self.occur[v].len() {
+                let id = self.occur[v][i];
+                let len = self.clause[id].len();
+
+                if (self.literal(id, v) > 0) != value {
+                    self.false_literal[id] += 1;
+                    if self.false_literal[id] == 1 { self.residual -= len - self.true_literal[id]; }
+                    continue;
+                }
+
+                self.true_literal[id] += 1;
+                if self.false_literal[id] > 0 { continue; }
+                self.residual -= 1;
+
+                if self.true_literal[id] == len { violated = true; }
+                if self.true_literal[id] + 1 == len {
+                    let l = *self.clause[id].iter().find(|l| self.value[l.unsigned_abs()].is_none()).unwrap();
+                    queue.push((l.unsigned_abs(), l < 0));
+                }
+            }
+            if violated { return false; }
+        }
+
+        true
+    }
+
+    fn undo(&mut self, mark: usize) {
+        while self.trail.len() > mark {
+            let v = self.trail.pop().unwrap();
+            let value = self.value[v].take().unwrap();
+
+            for i in 0..self.occur[v].len() {
+                let id = self.occur[v][i];
+                if (self.literal(id, v) > 0) != value {
+                    self.false_literal[id] -= 1;
+                    if self.false_literal[id] == 0 { self.residual += self.clause[id].len() - self.true_literal[id]; }
+                } else {
+                    self.true_literal[id] -= 1;
+                    if self.false_literal[id] == 0 { self.residual += 1; }
+                }
+            }
+        }
+    }
+
+    // the unassigned variables among `vars` split by the clauses not
+    // dropped: how many are in none of them, and the connected components of
+    // the rest as sorted variables
+    fn split(&mut self, vars: &[usize]) -> (usize, Vec<Vec<usize>>) {
+        self.round += 1;
+        let mut free = 0;
+        let mut parts = Vec::new();
+
+        for &s in vars {
+            if self.value[s].is_some() || self.mark[s] == self.round { continue; }
+            self.mark[s] = self.round;
+
+            let mut part = vec![s];
+            let mut i = 0;
+            while i < part.len() {
+                let x = part[i];
+                i += 1;
+                for &id in &self.occur[x] {
+                    if self.false_literal[id] > 0 { continue; }
+                    for l in &self.clause[id] {
+                        let y = l.unsigned_abs();
+                        if self.value[y].is_none() && self.mark[y] != self.round {
+                            self.mark[y] = self.round;
+                            part.push(y);
+                        }
+                    }
+                }
+            }
+
+            // a clause left with one unassigned literal has been propagated
+            if part.len() == 1 { free += 1; } else { part.sort(); parts.push(part); }
+        }
+
+        (free, parts)
+    }
+
+    // the cache key of a component, and how often each of its variables
+    // occurs in the clauses not dropped
+    fn normalise(&self, part: &[usize]) -> (Vec<isize>, Vec<usize>) {
+        let mut ids: Vec<usize> = part.iter()
+            .flat_map(|&v| self.occur[v].iter().copied())
+            .filter(|&id| self.false_literal[id] == 0)
+            .collect();
+        ids.sort();
+        ids.dedup();
+
+        let mut occur = crate::new_vector(part.len(), 0);
+        let mut clauses: Vec<Vec<isize>> = ids.into_iter().map( |id| {
+            self.clause[id].iter()
+                .filter(|l| self.value[l.unsigned_abs()].is_none())
+                .map( |&l| {
+                    let v = part.binary_search(&l.unsigned_abs()).unwrap();
+                    occur[v] += 1;
+                    l.signum() * (v as isize + 1)
+                } ).collect()
+        } ).collect();
+        clauses.sort();
+        clauses.dedup();
+
+        let mut key = Vec::new();
+        for c in clauses { key.extend(c); key.push(0); }
+        (key, occur)
+    }
+
+    // among the variables occurring most often, the one whose two branches
+    // leave the fewest literals after unit propagation; a branch running
+    // into a violated clause leaves none
+    fn branch_variable(&mut self, part: &[usize], occur: &[usize]) -> usize {
+        let mut candidate: Vec<usize> = (0..part.len()).collect();
+        candidate.sort_by_key(|&i| std::cmp::Reverse(occur[i]));
+        candidate.truncate(LOOKAHEAD);
+
+        let mut best = (usize::MAX, 0);
+        for i in candidate {
+            let v = part[i];
+            let mut left = 0;
+            for value in [true, false] {
+                let mark = self.trail.len();
+                if self.assign(v, value) { left += self.residual; }
+                self.undo(mark);
+            }
+            if left < best.0 { best = (left, v); }
+        }
+        best.1
+    }
+
+    // the weight of the assignments on the trail from `mark` on, times the
+    // total weight of the assignments of what is left of `vars`
+    fn count_rest(&mut self, vars: &[usize], mark: usize) -> Option<W> {
+        let mut ret = W::one();
+        for &v in &self.trail[mark..] { ret = ret.times(&W::set(self.bias[v - 1], self.value[v].unwrap())); }
+
+        let (free, parts) = self.split(vars);
+        ret = ret.times(&W::free(free));
+
+        for part in parts {
+            if ret.is_zero() { break; }
+            ret = ret.times(&self.count_component(&part)?);
+        }
+        Some(ret)
+    }
+
+    fn count_component(&mut self, part: &[usize]) -> Option<W> {
+        let (clauses, occur) = self.normalise(part);
+        let bias: Vec<f64> = part.iter().map(|&v| self.bias[v - 1]).collect();
+        let key = (clauses, W::key(&bias));
+        if let Some(ret) = self.cache.get(&key) { return Some(ret.clone()); }
+
+        if self.budget < key.0.len() { return None; }
+        self.budget -= key.0.len();
+
+        let v = self.branch_variable(part, &occur);
+
+        let mut ret = W::zero();
+        for value in [true, false] {
+            if W::set(self.bias[v - 1], value).is_zero() { continue; }
+
+            let mark = self.trail.len();
+            let branch = if self.assign(v, value) { self.count_rest(part, mark) } else { Some(W::zero()) };
+            self.undo(mark);
+            ret = ret.plus(&branch?);
+        }
+
+        self.cache.insert(key, ret.clone());
+        Some(ret)
+    }
+
+    fn count(&mut self) -> Option<W> {
+        if self.clause.iter().any(|c| c.is_empty()) { return Some(W::zero()); }
+
+        for id in 0..self.clause.len() {
+            if self.clause[id].len() > 1 { continue; }
+            let l = self.clause[id][0];
+            if !self.assign(l.unsigned_abs(), l < 0) { return Some(W::zero()); }
+        }
+
+        let vars: Vec<usize> = (1..self.value.len()).collect();
+        self.count_rest(&vars, 0)
+    }
+}
+
+// `budget` bounds the literals of the components the search branches on,
+// None once it runs out
+pub fn count_solutions(sat: &SAT, budget: usize) -> Option<BigCount> {
+    Counter::<BigCount>::new(sat, budget).count()
+}
+
+// P(no clause is violated) when variables are biased
+pub fn solution_density(sat: &SAT, budget: usize) -> Option<f64> {
+    Counter::<f64>::new(sat, budget).count()
+}
@@ -0,0 +1,193 @@
+use std::cell::OnceCell;
+
+use crate::sat::SAT;
+use crate::dep::{self, DependencyGraph};
+use crate::lll::{self, InvalidReason, PredictedResult};
+use crate::r#match::Match;
+use crate::grouping::Grouping;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CostClass {
+    Polynomial,
+    Exponential,
+}
+
+pub trait Criterion {
+    // column name in the csv output
+    fn name(&self) -> &'static str;
+    // key accepted by --filter
+    fn key(&self) -> String {
+        self.name().to_lowercase()
+    }
+    fn cost(&self) -> CostClass;
+    fn evaluate(&self, instance: &Evaluation) -> PredictedResult;
+}
+
+// what the criteria of one instance share: New and Con (and their MW
+// variants) are compared on the same matching, built once on first use
+pub struct Evaluation<'a> {
+    pub sat: &'a SAT,
+    pub dep: &'a DependencyGraph,
+    pub seed: u64,           // of the estimates
+    default_match: OnceCell<Match>,
+    max_weight_match: OnceCell<Match>
+}
+
+impl<'a> Evaluation<'a> {
+    pub fn new(sat: &'a SAT, dep: &'a DependencyGraph, seed: u64) -> Self {
+        Self { sat, dep, seed, default_match: OnceCell::new(), max_weight_match: OnceCell::new() }
+    }
+
+    pub fn default_match(&self) -> &Match {
+        self.default_match.get_or_init( || default_match(self.sat) )
+    }
+
+    pub fn max_weight_match(&self) -> &Match {
+        self.max_weight_match.get_or_init( || Match::from_sat_max_weight(self.sat) )
+    }
+}
+
+pub fn default_match(sat: &SAT) -> Match {
+    Match::from_sat_indexed(sat)
+}
+
+// the matched graphs keep one event per clause (the quotient at least one per
+// pair), so instances Shearer cannot take are rejected before any matching is built
+fn too_large(events: usize) -> bool {
+    events > lll::SHEARER_LIMIT
+}
+
+pub struct SymmetricLLL;
+
+impl Criterion for SymmetricLLL {
+    fn name(&self) -> &'static str { "LLL" }
+    fn cost(&self) -> CostClass { CostClass::Polynomial }
+    fn evaluate(&self, instance: &Evaluation) -> PredictedResult {
+        lll::symmertric_lll_checker(instance.dep)
+    }
+}
+
+pub struct AsymmetricLLL;
+
+impl Criterion for AsymmetricLLL {
+    fn name(&self) -> &'static str { "ASY" }
+    fn cost(&self) -> CostClass { CostClass::Polynomial }
+    fn evaluate(&self, instance: &Evaluation) -> PredictedResult {
+        lll::asymmetric_lll_checker(instance.dep)
+    }
+}
+
+pub struct Shearer;
+
+impl Criterion for Shearer {
+    fn name(&self) -> &'static str { "SHE" }
+    fn cost(&self) -> CostClass { CostClass::Exponential }
+    fn evaluate(&self, instance: &Evaluation) -> PredictedResult {
+        lll::shearers_bound_checker(instance.dep)
+    }
+}
+
+pub struct MatchedShearer;
+
+impl Criterion for MatchedShearer {
+    fn name(&self) -> &'static str { "New" }
+    fn cost(&self) -> CostClass { CostClass::Exponential }
+    fn evaluate(&self, instance: &Evaluation) -> PredictedResult {
+        if too_large(instance.dep.n) { return PredictedResult::Invalid(InvalidReason::TooLarge); }
+        let (sat, mat) = (instance.sat, instance.default_match());
+        lll::shearers_bound_checker(&DependencyGraph::from_sat_with_match(sat, mat))
+    }
+}
+
+pub struct ConjectureShearer;
+
+impl Criterion for ConjectureShearer {
+    fn name(&self) -> &'static str { "Con" }
+    fn cost(&self) -> CostClass { CostClass::Exponential }
+    fn evaluate(&self, instance: &Evaluation) -> PredictedResult {
+        if too_large(instance.dep.n) { return PredictedResult::Invalid(InvalidReason::TooLarge); }
+        let (sat, mat) = (instance.sat, instance.default_match());
+        lll::shearers_bound_checker(&DependencyGraph::from_sat_with_match_conjecture(sat, mat))
+    }
+}
+
+pub struct MaxWeightMatchedShearer;
+
+impl Criterion for MaxWeightMatchedShearer {
+    fn name(&self) -> &'static str { "NewMW" }
+    fn cost(&self) -> CostClass { CostClass::Exponential }
+    fn evaluate(&self, instance: &Evaluation) -> PredictedResult {
+        if too_large(instance.dep.n) { return PredictedResult::Invalid(InvalidReason::TooLarge); }
+        let (sat, mat) = (instance.sat, instance.max_weight_match());
+        lll::shearers_bound_checker(&DependencyGraph::from_sat_with_match(sat, mat))
+    }
+}
+
+pub struct MaxWeightConjectureShearer;
+
+impl Criterion for MaxWeightConjectureShearer {
+    fn name(&self) -> &'static str { "ConMW" }
+    fn cost(&self) -> CostClass { CostClass::Exponential }
+    fn evaluate(&self, instance: &Evaluation) -> PredictedResult {
+        if too_large(instance.dep.n) { return PredictedResult::Invalid(InvalidReason::TooLarge); }
+        let (sat, mat) = (instance.sat, instance.max_weight_match());
+        lll::shearers_bound_checker(&DependencyGraph::from_sat_with_match_conjecture(sat, mat))
+    }
+}
+
+// matched pairs merged into single events, no correction needed; the bound
+// is on the steps of the algorithm resampling the merged events, so bench
+// checks it against the matched simulator
+pub struct MergedShearer;
+
+impl Criterion for MergedShearer {
+    fn name(&self) -> &'static str { "Mrg" }
+    fn cost(&self) -> CostClass { CostClass::Exponential }
+    fn evaluate(&self, instance: &Evaluation) -> PredictedResult {
+        if too_large(instance.dep.n.div_ceil(2)) { return PredictedResult::Invalid(InvalidReason::TooLarge); }
+        let (sat, mat) = (instance.sat, instance.default_match());
+        lll::shearers_bound_checker(&DependencyGraph::quotient(sat, mat))
+    }
+}
+
+// New with groups of up to k clauses instead of pairs
+pub struct GroupedShearer {
+    pub name: &'static str,
+    pub k: usize
+}
+
+impl Criterion for GroupedShearer {
+    fn name(&self) -> &'static str { self.name }
+    fn cost(&self) -> CostClass { CostClass::Exponential }
+    fn evaluate(&self, instance: &Evaluation) -> PredictedResult {
+        if too_large(instance.dep.n) { return PredictedResult::Invalid(InvalidReason::TooLarge); }
+        let grouping = Grouping::from_sat_greedy(instance.sat, self.k);
+        lll::shearers_bound_checker(&DependencyGraph::from_sat_with_grouping(instance.sat, &grouping, dep::MATCH_CONSTANT))
+    }
+}
+
+pub struct BruteForce;
+
+impl Criterion for BruteForce {
+    fn name(&self) -> &'static str { "BF" }
+    fn cost(&self) -> CostClass { CostClass::Exponential }
+    fn evaluate(&self, instance: &Evaluation) -> PredictedResult {
+        lll::satisfiability_checker(instance.sat, instance.seed)
+    }
+}
+
+pub fn registry() -> Vec<Box<dyn Criterion>> {
+    vec![
+        Box::new(SymmetricLLL),
+        Box::new(AsymmetricLLL),
+        Box::new(Shearer),
+        Box::new(MatchedShearer),
+        Box::new(ConjectureShearer),
+        Box::new(MaxWeightMatchedShearer),
+        Box::new(MaxWeightConjectureShearer),
+        Box::new(MergedShearer),
+        Box::new(GroupedShearer { name: "New3", k: 3 }),
+        Box::new(GroupedShearer { name: "New4", k: 4 }),
+        Box::new(BruteForce),
+    ]
+}
@@ -0,0 +1,80 @@
+use crate::sat::SAT;
+use crate::random_space::RandomSpace;
+
+// two-sided 99% normal quantile
+const Z99: f64 = 2.576;
+
+#[derive(Debug, Clone, Copy)]
+pub struct DensityEstimate {
+    pub mean: f64,
+    pub lower: f64,
+    pub upper: f64
+}
+
+impl DensityEstimate {
+    pub fn from_samples(w: &[f64]) -> Self {
+        let n = w.len() as f64;
+        let mean = w.iter().sum::<f64>() / n;
+        let var = w.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1.0).max(1.0);
+        let half = Z99 * (var / n).sqrt();
+
+        Self { mean, lower: (mean - half).max(0.0), upper: (mean + half).min(1.0) }
+    }
+}
+
+// Sequential importance sampling of P(no bad event) under a random assignment.
+// Variables are assigned in order, each drawn from its own distribution
+// restricted to the values that do not complete a violated clause; the weight,
+// the product of the probabilities of the allowed values, is an unbiased
+// estimate of the density, and stays informative when the density is tiny,
+// unlike the naive indicator of a uniform sample.
+pub fn sequential_importance_sampling<R>(sat: &SAT, samples: usize, random_space: &mut R) -> DensityEstimate
+    where R: RandomSpace
+{
+    let m = sat.variable_count();
+    let bias = sat.bias();
+
+    // clause literals without duplicates, tautologies can never be violated
+    let mut clause: Vec<Vec<isize>> = Vec::new();
+    for c in sat {
+        let mut lits: Vec<isize> = c.into_iter().copied().collect();
+        lits.sort();
+        lits.dedup();
+        if lits.iter().any(|l| lits.contains(&-l)) { continue; }
+        clause.push(lits);
+    }
+
+    // clauses indexed by the variable of their last literal in assignment order
+    let mut last = crate::new_vector(m, Vec::new());
+    for (id, c) in clause.iter().enumerate() {
+        if c.is_empty() { return DensityEstimate { mean: 0.0, lower: 0.0, upper: 0.0 }; }
+        let v = c.iter().map(|l| l.unsigned_abs()).max().unwrap();
+        last[v - 1].push(id);
+    }
+
+    let mut var = crate::new_vector(m, false);
+    let mut weight = Vec::with_capacity(samples);
+
+    for _ in 0..samples {
+        let mut w = 1.0;
+
+        for v in 0..m {
+            let allowed: Vec<bool> = [false, true].into_iter().filter(|&b| {
+                var[v] = b;
+                last[v].iter().all(|&id| {
+                    !clause[id].iter().all(|&l| (l > 0) == var[l.unsigned_abs() - 1])
+                })
+            }).collect();
+
+            match allowed.len() {
+                0 => { w = 0.0; break; }
+                1 => { var[v] = allowed[0]; w *= if allowed[0] { bias[v] } else { 1.0 - bias[v] }; }
+                _ => { var[v] = random_space.fetch_biased_bit(v + 1, bias[v]).unwrap(); }
+            }
+        }
+
+        weight.push(w);
+    }
+
+    DensityEstimate::from_samples(&weight)
+}
@@ -1,7 +1,13 @@
 use crate::sat::SAT;
 use crate::r#match::Match;
+use crate::grouping::Grouping;
 use std::collections::BTreeSet;
 
+// the pr^2 / c correction of a matched pair, proven for 13 and conjectured
+// for 2
+pub const MATCH_CONSTANT: f64 = 13.0;
+pub const CONJECTURE_CONSTANT: f64 = 2.0;
+
 pub struct DependencyGraph {
     pub n: usize,    // number of vertices
     pub m: usize,    // number if edges
@@ -17,19 +23,9 @@ impl DependencyGraph {
         let n = sat.size();
         let mut edge  = Vec::new();
         let mut gamma = Vec::new();
-        let p = if sat.size() >= 100 {
-            crate::new_vector(n, 0.0 )
-        } else {
-            match sat.clause_size() {
-                0 => { sat.into_iter().map(|c| 1.0 / (1 << c.size()) as f64).collect() }
-                _ => { crate::new_vector(n, 1.0 / (1 << sat.clause_size()) as f64 ) }
-            }
-        };
-        let mut max_d = 0;
-        let max_p = match sat.clause_size() {
-            0 => { crate::max_f64(&p) }
-            _ => { p[0] }
-        };
+        // linear in the literals, exact for every width and bias
+        let p: Vec<f64> = (0..n).map(|id| sat.pr_land_exact(&[id])).collect();
+        let max_p = crate::max_f64(&p);
 
         let mut cache = crate::new_vector(sat.variable_count(), BTreeSet::new());
 
@@ -42,8 +38,6 @@ impl DependencyGraph {
                 }
             }
 
-            max_d = max_d.max( neighbor.len() );
-            
             for &&pre in &neighbor {
                 edge.push((pre, id));
                 let qwq : &mut Vec<usize> = &mut gamma[pre];
@@ -58,6 +52,39 @@ impl DependencyGraph {
             }
         }
 
+        let max_d = gamma.iter().map( |g| g.len() ).max().unwrap_or(0);
+
+        Self {
+            n, m: edge.len(),
+            edge, gamma, p, max_d, max_p
+        }
+    }
+
+    // events given by the (0-based) variables they depend on and their
+    // probabilities, two events are adjacent when they share a variable
+    pub fn from_variable_sets(vars: &[Vec<usize>], p: Vec<f64>) -> DependencyGraph {
+        let n = vars.len();
+        let m = vars.iter().flatten().max().map_or(0, |&v| v + 1);
+
+        let mut cache = crate::new_vector(m, Vec::new());
+        for (id, vs) in vars.iter().enumerate() {
+            for &v in vs { cache[v].push(id); }
+        }
+
+        let mut edge = Vec::new();
+        let mut gamma = Vec::with_capacity(n);
+        for (id, vs) in vars.iter().enumerate() {
+            let neighbor: BTreeSet<usize> = vs.iter()
+                .flat_map( |&v| cache[v].iter().copied() )
+                .filter( |&other| other != id )
+                .collect();
+            edge.extend( neighbor.iter().filter(|&&other| other > id).map(|&other| (id, other)) );
+            gamma.push(neighbor.into_iter().collect::<Vec<usize>>());
+        }
+
+        let max_d = gamma.iter().map( |g| g.len() ).max().unwrap_or(0);
+        let max_p = p.iter().copied().fold(0.0, f64::max);
+
         Self {
             n, m: edge.len(),
             edge, gamma, p, max_d, max_p
@@ -65,35 +92,93 @@ impl DependencyGraph {
     }
 
     pub fn from_sat_with_match(sat: &SAT, mat: &Match) -> DependencyGraph {
+        Self::from_sat_with_constant(sat, mat, MATCH_CONSTANT)
+    }
+
+    pub fn from_sat_with_match_conjecture(sat: &SAT, mat: &Match) -> DependencyGraph {
+        Self::from_sat_with_constant(sat, mat, CONJECTURE_CONSTANT)
+    }
+
+    pub fn from_sat_with_constant(sat: &SAT, mat: &Match, constant: f64) -> DependencyGraph {
+        Self::from_sat_with_correction(sat, mat, |pr, _, _| pr * pr / constant)
+    }
+
+    // correction(pr, u, v) is subtracted from both p[u] and p[v] for every
+    // matched pair that is not disjoint
+    pub fn from_sat_with_correction<F>(sat: &SAT, mat: &Match, correction: F) -> DependencyGraph
+        where F: Fn(f64, usize, usize) -> f64
+    {
         let mut dep = Self::form_sat(sat);
 
         for &(u, v) in mat {
-            let pr = sat.pr_land(&[u,v].into());
-            if pr == (0.5f64).powi((sat.clause_size() * 2) as i32) 
+            if sat.is_disjoint(u, v) 
               { continue; }
-            dep.p[u] -= pr * pr / 13.0;
-            dep.p[v] -= pr * pr / 13.0;
+            let pr = sat.pr_land_pair(u, v);
+            let c = correction(pr, u, v);
+            dep.p[u] -= c;
+            dep.p[v] -= c;
         }
 
         dep.max_p = crate::max_f64(&dep.p);
         dep
     }
 
-    pub fn from_sat_with_match_conjecture(sat: &SAT, mat: &Match) -> DependencyGraph {
+    pub fn from_sat_with_grouping(sat: &SAT, grouping: &Grouping, constant: f64) -> DependencyGraph {
+        // inclusion-exclusion over each group divided by the constant, for
+        // groups of two this is the pr^2/constant of from_sat_with_constant
         let mut dep = Self::form_sat(sat);
 
-        for &(u, v) in mat {
-            let pr = sat.pr_land(&[u,v].into());
-            if pr == (0.5f64).powi((sat.clause_size() * 2) as i32) 
-              { continue; }
-            dep.p[u] -= pr * pr / 2.0;
-            dep.p[v] -= pr * pr / 2.0;
+        for g in grouping {
+            for (&u, c) in g.iter().zip(Grouping::correction(sat, g)) {
+                dep.p[u] -= c / constant;
+            }
         }
 
         dep.max_p = crate::max_f64(&dep.p);
         dep
     }
 
+    pub fn quotient(sat: &SAT, mat: &Match) -> DependencyGraph {
+        // every matched pair becomes one event A \/ B, adjacent to whatever
+        // either clause was adjacent to; the probabilities of the merged
+        // events are exact
+        let dep = Self::form_sat(sat);
+
+        let mut block = crate::new_vector(dep.n, usize::MAX);
+        let mut p = Vec::new();
+        for &(u, v) in mat {
+            block[u] = p.len();
+            block[v] = p.len();
+            p.push(sat.pr_land_exact(&[u]) + sat.pr_land_exact(&[v]) - sat.pr_land_pair(u, v));
+        }
+        for (u, b) in block.iter_mut().enumerate() { if *b == usize::MAX {
+            *b = p.len();
+            p.push(sat.pr_land_exact(&[u]));
+        } }
+
+        let n = p.len();
+        let mut neighbor = crate::new_vector(n, BTreeSet::new());
+        for &(a, b) in &dep.edge {
+            let (a, b) = (block[a], block[b]);
+            if a != b {
+                neighbor[a].insert(b);
+                neighbor[b].insert(a);
+            }
+        }
+
+        let edge: Vec<(usize, usize)> = neighbor.iter().enumerate()
+            .flat_map( |(a, nb)| nb.iter().filter(move |&&b| a < b).map(move |&b| (a, b)) )
+            .collect();
+        let gamma: Vec<Vec<usize>> = neighbor.into_iter().map( |nb| nb.into_iter().collect() ).collect();
+        let max_d = gamma.iter().map( |g| g.len() ).max().unwrap_or(0);
+        let max_p = crate::max_f64(&p);
+
+        Self {
+            n, m: edge.len(),
+            edge, gamma, p, max_d, max_p
+        }
+    }
+
     pub fn get_gamma(&self, index: usize) -> &Vec<usize> {
         &self.gamma[index]
     }
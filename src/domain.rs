@@ -0,0 +1,141 @@
+use std::collections::BTreeSet;
+
+use crate::sat::SAT;
+use crate::event::{BadEvent, Instance};
+use crate::random_space::RandomSpace;
+
+// Variables take values in 0..d with their own distribution, variables are
+// 0-based here. An event happens when the values of its variables form one
+// of its bad tuples.
+#[derive(Clone, Debug)]
+pub struct FiniteEvent {
+    vars: Vec<usize>,
+    bad: Vec<Vec<usize>>
+}
+
+impl FiniteEvent {
+    pub fn new(vars: Vec<usize>, mut bad: Vec<Vec<usize>>) -> Self {
+        debug_assert!( bad.iter().all(|t| t.len() == vars.len()) );
+        bad.sort();
+        bad.dedup();
+        Self { vars, bad }
+    }
+
+    // every tuple of values of `vars` on which `pred` holds is bad
+    pub fn from_predicate<F>(vars: Vec<usize>, domain: &[usize], pred: F) -> Self
+        where F: Fn(&[usize]) -> bool
+    {
+        let mut bad = Vec::new();
+        let mut tuple = crate::new_vector(vars.len(), 0);
+
+        'enumerate: loop {
+            if pred(&tuple) { bad.push(tuple.clone()); }
+
+            // next tuple in mixed radix
+            for i in 0..vars.len() {
+                tuple[i] += 1;
+                if tuple[i] < domain[vars[i]] { continue 'enumerate; }
+                tuple[i] = 0;
+            }
+            break;
+        }
+
+        Self::new(vars, bad)
+    }
+}
+
+impl BadEvent for FiniteEvent {
+    type Value = usize;
+    // P(x = a) for every value a
+    type Distribution = Vec<f64>;
+
+    fn variables(&self) -> Vec<usize> {
+        self.vars.clone()
+    }
+
+    fn check_violated(&self, value: &[usize]) -> bool {
+        let tuple: Vec<usize> = self.vars.iter().map(|&v| value[v]).collect();
+        self.bad.binary_search(&tuple).is_ok()
+    }
+
+    fn probability(&self, distribution: &[Vec<f64>]) -> f64 {
+        self.bad.iter().map( |t| 
+            self.vars.iter().zip(t).map(|(&v, &a)| distribution[v][a]).product::<f64>()
+        ).sum()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FiniteInstance {
+    distribution: Vec<Vec<f64>>,
+    event: Vec<FiniteEvent>
+}
+
+impl FiniteInstance {
+    pub fn new(distribution: Vec<Vec<f64>>, event: Vec<FiniteEvent>) -> Self {
+        debug_assert!( distribution.iter().all(|d| !d.is_empty() && (d.iter().sum::<f64>() - 1.0).abs() < 1e-9) );
+        Self { distribution, event }
+    }
+
+    pub fn uniform(m: usize, d: usize) -> Vec<Vec<f64>> {
+        crate::new_vector(m, crate::new_vector(d, 1.0 / d as f64))
+    }
+
+    // the clauses of a CNF as events over boolean variables
+    pub fn from_sat(sat: &SAT) -> Self {
+        let distribution = sat.bias().iter().map(|&q| vec![1.0 - q, q]).collect();
+
+        let event = sat.into_iter().map( |c| {
+            let lits: BTreeSet<isize> = c.into_iter().copied().collect();
+            let vars: Vec<usize> = lits.iter().map(|l| l.unsigned_abs() - 1).collect::<BTreeSet<_>>().into_iter().collect();
+            let tuple: Vec<usize> = vars.iter().map(|&v| lits.contains(&(v as isize + 1)) as usize).collect();
+            // x and -x can not both be true
+            let bad = if vars.len() == lits.len() { vec![tuple] } else { Vec::new() };
+            FiniteEvent::new(vars, bad)
+        } ).collect();
+
+        Self::new(distribution, event)
+    }
+}
+
+impl Instance for FiniteInstance {
+    type Event = FiniteEvent;
+
+    fn events(&self) -> &[FiniteEvent] {
+        &self.event
+    }
+
+    fn distribution(&self) -> &[Vec<f64>] {
+        &self.distribution
+    }
+
+    fn sample<R: RandomSpace>(&self, v: usize, random_space: &mut R) -> Option<usize> {
+        random_space.fetch_value(v + 1, &self.distribution[v])
+    }
+
+    fn log2_density(&self) -> Option<f64> {
+        // enumerates every assignment, only for small instances
+        const LIMITS: f64 = (1 << 20) as f64;
+        if self.distribution.iter().map(|d| d.len() as f64).product::<f64>() > LIMITS 
+          { return None; }
+
+        let m = self.variable_count();
+        let mut value = crate::new_vector(m, 0);
+        let mut density = 0.0;
+
+        'enumerate: loop {
+            if self.event.iter().all(|e| !e.check_violated(&value)) {
+                density += value.iter().enumerate().map(|(v, &a)| self.distribution[v][a]).product::<f64>();
+            }
+
+            for (a, d) in value.iter_mut().zip(&self.distribution) {
+                *a += 1;
+                if *a < d.len() { continue 'enumerate; }
+                *a = 0;
+            }
+            break;
+        }
+
+        Some(density.log2())
+    }
+}
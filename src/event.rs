@@ -0,0 +1,82 @@
+use crate::dep::DependencyGraph;
+use crate::density::DensityEstimate;
+use crate::random_space::RandomSpace;
+
+// An event of the variable setting: it is determined by the values of a set
+// of variables, and its probability follows from their distributions.
+// Variables are 0-based.
+pub trait BadEvent {
+    type Value: Copy + Default;
+    // distribution of a single variable
+    type Distribution;
+
+    fn variables(&self) -> Vec<usize>;
+    // the variables MT resamples when this event is picked, in the order
+    // they draw their bits
+    fn resampled_variables(&self) -> Vec<usize> {
+        self.variables()
+    }
+    fn check_violated(&self, value: &[Self::Value]) -> bool;
+    // exact when it can be computed, an estimate otherwise
+    fn probability(&self, distribution: &[Self::Distribution]) -> f64;
+}
+
+pub type Value<I> = <<I as Instance>::Event as BadEvent>::Value;
+pub type Distribution<I> = <<I as Instance>::Event as BadEvent>::Distribution;
+
+// a family of bad events over independent variables
+pub trait Instance: Clone {
+    type Event: BadEvent;
+
+    fn events(&self) -> &[Self::Event];
+    fn distribution(&self) -> &[Distribution<Self>];
+    // a fresh value of variable v, drawn from the bits of variable v + 1
+    fn sample<R: RandomSpace>(&self, v: usize, random_space: &mut R) -> Option<Value<Self>>;
+
+    fn size(&self) -> usize {
+        self.events().len()
+    }
+
+    fn variable_count(&self) -> usize {
+        self.distribution().len()
+    }
+
+    fn probability(&self, id: usize) -> f64 {
+        self.events()[id].probability(self.distribution())
+    }
+
+    fn check_violated(&self, value: &[Value<Self>]) -> Vec<bool> {
+        self.events().iter().map(|e| e.check_violated(value)).collect()
+    }
+
+    fn resample<R: RandomSpace>(&self, value: &mut [Value<Self>], random_space: &mut R, list: &[usize]) -> Result<(),()> {
+        for &v in list {
+            value[v] = self.sample(v, random_space).ok_or(())?;
+        }
+        Ok(())
+    }
+
+    fn dependency_graph(&self) -> DependencyGraph {
+        let vars: Vec<Vec<usize>> = self.events().iter().map(|e| e.variables()).collect();
+        let p = (0..self.size()).map(|id| self.probability(id)).collect();
+        DependencyGraph::from_variable_sets(&vars, p)
+    }
+
+    // log2 P(no bad event) if it can be computed exactly
+    fn log2_density(&self) -> Option<f64> {
+        None
+    }
+
+    // P(no bad event) by rejection sampling
+    fn estimate_density<R: RandomSpace>(&self, samples: usize, random_space: &mut R) -> DensityEstimate {
+        let all: Vec<usize> = (0..self.variable_count()).collect();
+        let mut value = crate::new_vector(self.variable_count(), Value::<Self>::default());
+
+        let hit: Vec<f64> = (0..samples).map( |_| {
+            self.resample(&mut value, random_space, &all).unwrap();
+            if self.check_violated(&value).iter().any(|&b| b) { 0.0 } else { 1.0 }
+        } ).collect();
+
+        DensityEstimate::from_samples(&hit)
+    }
+}
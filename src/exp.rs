@@ -1,21 +1,34 @@
-use std::f64::NAN;
-
 use crate::lll::PredictedResult;
 use crate::moser_tardos_algorithm::BenchResult;
 use crate::sat;
 use crate::lll;
 use crate::dep;
 use crate::moser_tardos_algorithm;
-use crate::r#match;
+use crate::criterion::{self, Criterion};
 use crate::random_space;
+use crate::cdcl;
+use crate::match_search;
+use crate::domain;
+use crate::hypergraph;
+use crate::graph;
+use crate::event::Instance;
+
+// master seed of the experiments when --seed is not given; instance id gets
+// the stream derive_seed(seed, id), printed in its row, and every simulator
+// and repetition derives its own stream from that. --id=<id> reruns a single
+// row, with --row-seed=<seed> under the seed printed in it
+pub const DEFAULT_SEED: u64 = 0;
 
 pub struct DataSet {
     dataset_name: String,
-    data_names: Vec<String>
+    data_names: Vec<String>,
+    bias: Option<f64>,   // P(x_i = 1) of every variable, fair coins if None
+    row: Option<(usize, Option<u64>)>
 }
 
 pub struct DataSetIterator<'a> {
-    inner: std::slice::Iter<'a, String>
+    inner: std::slice::Iter<'a, String>,
+    bias: Option<f64>
 }
 
 impl<'a> Iterator for DataSetIterator<'a> {
@@ -25,25 +38,92 @@ impl<'a> Iterator for DataSetIterator<'a> {
             None => { None }
             Some( str ) => {
                 let path = format!("./dataset/{str}");
-                Some( sat::SAT::from_file(&path) )
+                let sat = sat::SAT::from_file(&path);
+                match self.bias {
+                    None => { Some(sat) }
+                    Some(q) => { 
+                        let m = sat.variable_count();
+                        Some( sat.with_bias(crate::new_vector(m, q)) ) 
+                    }
+                }
             }
         }
     }
 }
 
-impl<'a> IntoIterator for &'a DataSet {
-    type Item = sat::SAT;
-    type IntoIter = DataSetIterator<'a>;
-    fn into_iter(self) -> Self::IntoIter {
+impl DataSet {
+    fn size(&self) -> usize {
+        self.data_names.len()
+    }
+
+    pub fn with_bias(mut self, q: f64) -> Self {
+        self.bias = Some(q);
+        self
+    }
+
+    // only the instance id, under `seed` instead of the seed derived for it
+    // when given
+    pub fn with_row(mut self, id: usize, seed: Option<u64>) -> Self {
+        assert!( id < self.size(), "no such instance" );
+        self.row = Some((id, seed));
+        self
+    }
+
+    fn selected(&self) -> &[String] {
+        match self.row {
+            None => { &self.data_names }
+            Some((id, _)) => { &self.data_names[id..=id] }
+        }
+    }
+
+    // id and seed of every row, in the order the loaders give the instances
+    fn rows(&self, seed: u64) -> impl Iterator<Item = (usize, u64)> + '_ {
+        let ids = match self.row {
+            None => { 0..self.size() }
+            Some((id, _)) => { id..id + 1 }
+        };
+        ids.map( move |id| match self.row {
+            Some((_, Some(s))) => { (id, s) }
+            _ => { (id, random_space::derive_seed(seed, id as u64)) }
+        } )
+    }
+
+    // datasets of CNFs list .cnf or .sat files
+    fn is_cnf(&self) -> bool {
+        self.data_names.iter().all( |s| s.ends_with(".cnf") || s.ends_with(".sat") )
+    }
+
+    // datasets of hypergraphs list .hg files
+    fn is_hypergraph(&self) -> bool {
+        self.data_names.iter().all( |s| s.ends_with(".hg") )
+    }
+
+    fn sats(&self) -> DataSetIterator<'_> {
+        assert!( self.is_cnf(), "Should be a dataset of CNFs" );
         DataSetIterator {
-            inner: self.data_names.iter()
+            inner: self.selected().iter(),
+            bias: self.bias
         }
     }
-}
 
-impl DataSet {
-    fn size(&self) -> usize {
-        self.data_names.len()
+    // datasets of graphs list DIMACS .col files or .edges edge lists
+    fn graphs(&self) -> impl Iterator<Item = graph::Graph> + '_ {
+        assert!( self.data_names.iter().all( |s| s.ends_with(".col") || s.ends_with(".edges") ), "Should be a dataset of graphs" );
+        self.selected().iter().map( |str| graph::Graph::from_file(&format!("./dataset/{str}")) )
+    }
+
+    fn hypergraphs(&self) -> impl Iterator<Item = hypergraph::Hypergraph> + '_ {
+        assert!( self.is_hypergraph(), "Should be a dataset of hypergraphs" );
+        self.selected().iter().map( |str| {
+            let g = hypergraph::Hypergraph::from_file(&format!("./dataset/{str}"));
+            match self.bias {
+                None => { g }
+                Some(q) => {
+                    let n = g.variable_count();
+                    g.with_bias(crate::new_vector(n, q))
+                }
+            }
+        } )
     }
 }
 
@@ -54,162 +134,277 @@ pub fn load_dataset(name: &str) -> DataSet {
 
     DataSet {
         dataset_name: String::from(name),
-        data_names: content.split("\n").map(|s| String::from(s)).collect()
+        data_names: content.split("\n").map(|s| String::from(s)).collect(),
+        bias: None,
+        row: None
     }
 }
 
-pub fn bench(dataset: &DataSet, turn: usize, data_filter: &str) {
-    println!( "id, LLL, SHE, New, Con, BF, mt_mean, mt_sigma, z1, z2, z3, z4" );
+fn z_score(predicted: &PredictedResult, mean: f64, se: f64) -> f64 {
+    // an estimated prediction is compared through the nearest end of its interval
+    match *predicted {
+        PredictedResult::Invalid(_) => { f64::NAN },
+        PredictedResult::UpperBound(p) => { (mean - p) / se },
+        PredictedResult::Estimate { lower, upper, .. } => {
+            if mean < lower { (mean - lower) / se }
+            else if mean > upper { (mean - upper) / se }
+            else { 0.0 }
+        }
+    }
+}
+
+fn evaluate_criteria(
+    criteria: &[Box<dyn Criterion>], 
+    sat: &sat::SAT, 
+    dep: &dep::DependencyGraph, 
+    data_filter: &str,
+    seed: u64) -> Option<Vec<lll::TimedResult>> 
+{
+    // the filtered criterion goes first and cheap ones before expensive ones,
+    // so a skipped instance never pays for the exponential checkers
+    let mut order: Vec<usize> = (0..criteria.len()).collect();
+    order.sort_by_key( |&i| (criteria[i].key() != data_filter, criteria[i].cost()) );
+
+    let mut results: Vec<Option<lll::TimedResult>> = (0..criteria.len()).map( |_| None ).collect();
+    // the matchings are shared, their cost goes to the first criterion using them
+    let instance = criterion::Evaluation::new(sat, dep, seed);
+
+    for i in order {
+        let r = lll::timed( || criteria[i].evaluate(&instance) );
+        if criteria[i].key() == data_filter && r.result.is_invalid() { return None; }
+        results[i] = Some(r);
+    }
+
+    Some( results.into_iter().map( |r| r.unwrap() ).collect() )
+}
+
+// the columns of a bench: the criteria, the simulators with plain MT first,
+// the z name comparing each later simulator to MT, and (z name, criterion,
+// simulator) for criteria checked against a later simulator instead of MT
+struct BenchColumns {
+    criteria: Vec<&'static str>,
+    runs: Vec<&'static str>,
+    z_runs: Vec<&'static str>,
+    cross: Vec<(&'static str, usize, usize)>
+}
+
+// stream of the instance seed for the estimates of the criteria, the
+// simulators take the streams 0, 1 and 2
+const CRITERIA_STREAM: u64 = 3;
+
+// the rows of a bench; `evaluate` gives the criteria results and simulator
+// runs of an instance under its seed, or None when the filter skips it
+
+fn bench_rows<T, F>(dataset: &DataSet, columns: BenchColumns, instances: T, turn: usize, seed: u64, mut evaluate: F) 
+    where T: Iterator, F: FnMut(&T::Item, u64) -> Option<(Vec<lll::TimedResult>, Vec<BenchResult>)>
+{
+    eprintln!("seed: {seed}");
+
+    let names = columns.criteria.join(", ");
+    let runs = columns.runs.iter().map( |x| format!("{x}_mean, {x}_sigma") ).collect::<Vec<_>>().join(", ");
+    let z_labels = columns.criteria.iter()
+        .chain(columns.z_runs.iter())
+        .chain(columns.cross.iter().map( |c| &c.0 ))
+        .collect::<Vec<_>>();
+    let z_names = z_labels.iter().map( |x| format!("z_{x}") ).collect::<Vec<_>>().join(", ");
+    let t_names = columns.criteria.iter().map( |x| format!("t_{x}") ).collect::<Vec<_>>().join(", ");
+    println!( "id, seed, {names}, {runs}, {z_names}, {t_names}" );
 
     let mut skipped_cnt = 0;
     let mut failed_cnt = 0;
-    let mut c1 = 0; let mut d1 = 0; let mut e1 = 0;
-    let mut c2 = 0; let mut d2 = 0; let mut e2 = 0;
-    let mut c3 = 0; let mut d3 = 0; let mut e3 = 0;
-    let mut c4 = 0; let mut d4 = 0; let mut e4 = 0; 
+    // pass, fail, nan for every criterion and for the algorithm comparisons
+    let mut stat = crate::new_vector(z_labels.len(), (0, 0, 0));
 
     let sqn = (turn as f64).sqrt();
     let z99 = 2.33;
 
-    for (id, sat) in dataset.into_iter().enumerate() {
+    for ((id, seed), instance) in dataset.rows(seed).zip(instances) {
         eprintln!( "bench on {} {}/{}", dataset.dataset_name, id + 1, dataset.size() );
 
-        let mat = if sat.size() <= 10000 
-            { r#match::Match::from_sat_greedy(&sat) }
-        else 
-            { r#match::Match::from_random(sat.size()) };
-        
-        let dep = dep::DependencyGraph::form_sat(&sat);
+        let (results, runs) = match evaluate(&instance, seed) {
+            None => {
+                println!("{id},{seed},skipped");
+                skipped_cnt += 1;
+                continue;
+            }
+            Some(row) => row
+        };
 
-        let lll = lll::symmertric_lll_checker(&dep);
-        let she = lll::shearers_bound_checker(&dep);
+        let stats = runs.iter().map( |r| match *r {
+            BenchResult::Failed => { None }
+            BenchResult::Success { mean, sigma } => { Some((mean, sigma)) }
+        } ).collect::<Option<Vec<(f64, f64)>>>();
+        let stats = match stats {
+            None => { 
+                failed_cnt += 1;
+                println!("{id},{seed},failed"); continue; 
+            }
+            Some(stats) => stats
+        };
+        let (mean_x, sigma_x) = stats[0];
 
-        let dep = dep::DependencyGraph::from_sat_with_match(&sat, &mat);
-        let new = lll::shearers_bound_checker(&dep);
+        let mut z = results.iter()
+            .map( |r| z_score(&r.result, mean_x, sigma_x / sqn) )
+            .collect::<Vec<_>>();
+        for &(mean_y, sigma_y) in &stats[1..] {
+            z.push( (mean_y - mean_x) / ( (sigma_x * sigma_x + sigma_y * sigma_y).sqrt() / sqn ) );
+        }
+        for &(_, c, r) in &columns.cross {
+            let (mean_w, sigma_w) = stats[r];
+            z.push( z_score(&results[c].result, mean_w, sigma_w / sqn) );
+        }
 
-        let dep = dep::DependencyGraph::from_sat_with_match_conjecture(&sat, &mat);
-        let con = lll::shearers_bound_checker(&dep);
+        for (zi, (c, e, d)) in z.iter().zip(stat.iter_mut()) {
+            if zi.is_nan() { *d += 1; }
+            else if *zi < z99 { *c += 1; }
+            else { *e += 1; }
+        }
 
-        let bf = lll::satisfiability_checker(&sat);
+        let bounds = results.iter().map( |r| format!("{r}") ).collect::<Vec<_>>().join(",");
+        let runs = runs.iter().map( |r| format!("{r}") ).collect::<Vec<_>>().join(",");
+        let z = z.iter().map( |zi| format!("{zi:.3}") ).collect::<Vec<_>>().join(",");
+        let times = results.iter()
+            .map( |r| format!("{:.6}", r.runtime.as_secs_f64()) )
+            .collect::<Vec<String>>().join(",");
 
-        let skiped = match data_filter {
-            "lll" => { lll == PredictedResult::Invalid },
-            "she" => { she == PredictedResult::Invalid },
-            "new" => { new == PredictedResult::Invalid },
-            "con" => { con == PredictedResult::Invalid },
-            "bf"  => { bf  == PredictedResult::Invalid },
-            _ => {false}
-        };
+        println!("{id},{seed},{bounds},{runs},{z},{times}")
+    }
+    eprintln!("skipped: {skipped_cnt}");
+    eprintln!("failed: {failed_cnt}");
+    for (name, (c, e, d)) in z_labels.iter().zip(stat.iter()) {
+        eprintln!("z_{name} : pass: {c} fail: {e} nan: {d}");
+    }
+}
 
-        if skiped {
-            println!("{id}, skipped");
-            skipped_cnt += 1;
-            continue;
-        }
+pub fn bench(dataset: &DataSet, turn: usize, data_filter: &str, seed: u64) {
+    if dataset.is_hypergraph() 
+      { return bench_events(dataset, dataset.hypergraphs(), turn, data_filter, seed); }
+
+    let criteria = criterion::registry();
+    let new_id = criteria.iter().position( |c| c.key() == "new" ).unwrap();
+    let mrg_id = criteria.iter().position( |c| c.key() == "mrg" ).unwrap();
+
+    let columns = BenchColumns {
+        criteria: criteria.iter().map( |c| c.name() ).collect(),
+        runs: vec!["mt", "new", "pair"],
+        z_runs: vec!["alg", "pair"],
+        cross: vec![("pair_New", new_id, 2), ("pair_Mrg", mrg_id, 2)]
+    };
+
+    bench_rows(dataset, columns, dataset.sats(), turn, seed, |sat, seed| {
+        let dep = dep::DependencyGraph::form_sat(sat);
+
+        let results = evaluate_criteria(&criteria, sat, &dep, data_filter, random_space::derive_seed(seed, CRITERIA_STREAM))?;
 
         let mt = 
             moser_tardos_algorithm::bench_algorithm::<
                 moser_tardos_algorithm::MTsAlgorithmSimulator<
                     random_space::InfiniteRandomSpace
                 >
-            >(&sat, turn);
+            >(sat, turn, random_space::derive_seed(seed, 0));
 
         let pmt = 
             moser_tardos_algorithm::bench_algorithm::<
                 moser_tardos_algorithm::NewAlgorithmSimulator<
                     random_space::InfiniteRandomSpace
                 >
-            >(&sat, turn);
-        
-        let (mean_x, sigma_x) = match mt {
-            BenchResult::Failed => { 
-                failed_cnt += 1;
-                println!("failed"); continue; 
-            }
-            BenchResult::Success { mean, sigma } => { ( mean, sigma) }
-        };
-
-        let (mean_y, sigma_y) = match pmt {
-            BenchResult::Failed => { 
-                failed_cnt += 1;
-                println!("failed"); continue; 
-            }
-            BenchResult::Success { mean, sigma } => { ( mean, sigma) }
-        };
-
-        let z1 = match new {
-            PredictedResult::Invalid => { NAN },
-            PredictedResult::UpperBound(p1) => {(mean_x - p1) /  (sigma_x / sqn) }
-        };
+            >(sat, turn, random_space::derive_seed(seed, 1));
 
-        let z2 = match con {
-            PredictedResult::Invalid => { NAN },
-            PredictedResult::UpperBound(p2) => {(mean_x - p2) /  (sigma_x / sqn) }
-        };
-
-        let z3 = match bf {
-            PredictedResult::Invalid => { NAN },
-            PredictedResult::UpperBound(p3) => {(mean_x - p3) /  (sigma_x / sqn) }
-        };
+        let qmt = 
+            moser_tardos_algorithm::bench_algorithm::<
+                moser_tardos_algorithm::MatchedAlgorithmSimulator<
+                    random_space::InfiniteRandomSpace
+                >
+            >(sat, turn, random_space::derive_seed(seed, 2));
 
-        let z4 = (mean_y - mean_x) / ( (sigma_x * sigma_x + sigma_y * sigma_y).sqrt() / sqn );
+        Some((results, vec![mt, pmt, qmt]))
+    });
+}
 
-        if z1.is_nan() { d1 += 1; }
-        else if z1 < z99 { c1 += 1; }
-        else { e1 += 1; }
+type EventChecker<I> = fn(&I, &dep::DependencyGraph, u64) -> PredictedResult;
+
+// the criteria that need nothing but the events, the others are defined on
+// the matched clauses of a CNF
+fn event_criteria<I: Instance>() -> [(&'static str, EventChecker<I>); 4] {
+    [
+        ("LLL", |_, dep, _| lll::symmertric_lll_checker(dep)),
+        ("ASY", |_, dep, _| lll::asymmetric_lll_checker(dep)),
+        ("SHE", |_, dep, _| lll::shearers_bound_checker(dep)),
+        ("BF",  |instance, _, seed| lll::satisfiability_checker(instance, seed)),
+    ]
+}
 
-        if z2.is_nan() { d2 += 1; }
-        else if z2 < z99 { c2 += 1; }
-        else { e2 += 1; }
+// bench for instances that are not CNFs, with the MT and parallel MT runs
+fn bench_events<I, T>(dataset: &DataSet, instances: T, turn: usize, data_filter: &str, seed: u64) 
+    where I: Instance, T: Iterator<Item = I>
+{
+    let criteria = event_criteria::<I>();
+
+    let columns = BenchColumns {
+        criteria: criteria.iter().map( |c| c.0 ).collect(),
+        runs: vec!["mt", "new"],
+        z_runs: vec!["alg"],
+        cross: Vec::new()
+    };
+
+    bench_rows(dataset, columns, instances, turn, seed, |instance, seed| {
+        let dep = instance.dependency_graph();
+
+        let mut results = Vec::new();
+        for (name, checker) in &criteria {
+            let r = lll::timed( || checker(instance, &dep, random_space::derive_seed(seed, CRITERIA_STREAM)) );
+            if name.to_lowercase() == data_filter && r.result.is_invalid() { return None; }
+            results.push(r);
+        }
 
-        if z3.is_nan() { d3 += 1; }
-        else if z3 < z99 { c3 += 1; }
-        else { e3 += 1; }
+        let mt = 
+            moser_tardos_algorithm::bench_algorithm::<
+                moser_tardos_algorithm::MTsAlgorithmSimulator<
+                    random_space::InfiniteRandomSpace, I
+                >
+            >(instance, turn, random_space::derive_seed(seed, 0));
 
-        if z4.is_nan() { d4 += 1; }
-        else if z4 < z99 { c4 += 1; }
-        else { e4 += 1; }
+        let pmt = 
+            moser_tardos_algorithm::bench_algorithm::<
+                moser_tardos_algorithm::NewAlgorithmSimulator<
+                    random_space::InfiniteRandomSpace, I
+                >
+            >(instance, turn, random_space::derive_seed(seed, 1));
 
-        println!("{id},{lll},{she},{new},{con},{bf},{mt},{z1:.3},{z2:.3},{z3:.3},{z4:.3}")
-    }
-    eprintln!("skipped: {skipped_cnt}");
-    eprintln!("failed: {failed_cnt}");
-    eprintln!("z1 : pass: {c1} fail: {e1} nan: {d1}");
-    eprintln!("z2 : pass: {c2} fail: {e2} nan: {d2}");
-    eprintln!("z3 : pass: {c3} fail: {e3} nan: {d3}");
-    eprintln!("z4 : pass: {c4} fail: {e4} nan: {d4}");
+        Some((results, vec![mt, pmt]))
+    });
 }
 
-pub fn enum_step(dataset: &DataSet, turn: usize, data_filter: &str) {
+pub fn enum_step(dataset: &DataSet, turn: usize, data_filter: &str, seed: u64) {
     let str1 = (0..=turn).map( |x| format!("MT{x}") ).collect::<Vec<String>>().join(", ");
     let str2 = (0..=turn).map( |x| format!("New{x}") ).collect::<Vec<String>>().join(", ");
-    println!( "id, LLL, SHE, New, BF, {str1}, {str2}" );
+    let str4 = (0..=turn).map( |x| format!("PAIR{x}") ).collect::<Vec<String>>().join(", ");
+    let str3 = (0..=turn).map( |x| format!("LLLT{x}, ASYT{x}, SHET{x}, MTT{x}") ).collect::<Vec<String>>().join(", ");
+
+    let criteria = criterion::registry();
+    let names = criteria.iter().map( |c| c.name() ).collect::<Vec<_>>().join(", ");
+    eprintln!("seed: {seed}");
+    println!( "id, seed, {names}, {str1}, {str2}, {str4}, {str3}" );
 
-    for (id, sat) in dataset.into_iter().enumerate() {
+    for ((id, seed), sat) in dataset.rows(seed).zip(dataset.sats()) {
         eprintln!( "enum on {} {}/{}", dataset.dataset_name, id + 1, dataset.size() );
 
-        let mat = r#match::Match::from_sat_greedy(&sat);
         let dep = dep::DependencyGraph::form_sat(&sat);
 
-        let lll = lll::symmertric_lll_checker(&dep);
-        let she = lll::shearers_bound_checker(&dep);
-
-        let dep = dep::DependencyGraph::from_sat_with_match(&sat, &mat);
-        let new = lll::shearers_bound_checker(&dep);
-
-        let bf = lll::satisfiability_checker(&sat);
-
-        let skiped = match data_filter {
-            "lll" => { lll == PredictedResult::Invalid },
-            "she" => { she == PredictedResult::Invalid },
-            "new" => { new == PredictedResult::Invalid },
-            _ => {false}
+        let results = match evaluate_criteria(&criteria, &sat, &dep, data_filter, random_space::derive_seed(seed, CRITERIA_STREAM)) {
+            None => {
+                println!("{id},{seed},skiped");
+                continue;
+            }
+            Some(results) => results
         };
 
-        if skiped {
-            println!("{id}, skiped");
-            continue;
-        }
+        // P(MT runs >= x + 1 steps), comparable with the x-th MT column
+        let tail = (0..=turn).map( |x| [
+            lll::symmertric_lll_tail_bound(&dep, x + 1),
+            lll::asymmetric_lll_tail_bound(&dep, x + 1),
+            lll::shearers_tail_bound(&dep, x + 1),
+        ] ).collect::<Vec<_>>();
 
         let mt = 
             moser_tardos_algorithm::enum_algorithm::<
@@ -224,30 +419,357 @@ pub fn enum_step(dataset: &DataSet, turn: usize, data_filter: &str) {
                     random_space::LimitedRandomSpace
                 >
             >(&sat, turn);
+
+        let qmt = 
+            moser_tardos_algorithm::enum_algorithm::<
+                moser_tardos_algorithm::MatchedAlgorithmSimulator<
+                    random_space::LimitedRandomSpace
+                >
+            >(&sat, turn);
         
-        println!("{id},{lll},{she},{new},{bf},{mt},{pmt}")
+        let exact = match &mt {
+            moser_tardos_algorithm::EnumResult::Success { p } => { p.clone() }
+            moser_tardos_algorithm::EnumResult::Failed => { crate::new_vector(turn + 1, f64::NAN) }
+        };
+
+        let tail = tail.iter().zip(exact.iter()).map( |(predicted, exact)| {
+            let mut cols: Vec<String> = predicted.iter().map( |r| match r {
+                PredictedResult::Invalid(reason) => { format!("N/A:{reason}") }
+                PredictedResult::UpperBound(up) => { format!("{:.3}", up.log2()) }
+                PredictedResult::Estimate { value, .. } => { format!("{:.3}", value.log2()) }
+            } ).collect();
+            cols.push(format!("{:.3}", exact.log2()));
+            cols.join(",")
+        } ).collect::<Vec<String>>().join(",");
+
+        let bounds = results.iter().map( |r| format!("{r}") ).collect::<Vec<_>>().join(",");
+
+        println!("{id},{seed},{bounds},{mt},{pmt},{qmt},{tail}")
+
+    }
+}
+
+// correction constants tried by the sweep, in increasing order
+const SWEEP_CONSTANTS: [f64; 11] = [1.0, 1.5, 2.0, 3.0, 4.0, 6.0, 8.0, 13.0, 16.0, 32.0, 64.0];
+
+pub fn sweep(dataset: &DataSet, turn: usize, seed: u64) {
+    eprintln!("seed: {seed}");
+
+    let names = SWEEP_CONSTANTS.iter().map( |c| format!("C{c}") ).collect::<Vec<String>>().join(", ");
+    println!( "id, seed, mt_mean, mt_sigma, {names}" );
+
+    let sqn = (turn as f64).sqrt();
+    let z99 = 2.33;
+
+    // smallest constant failing the z-test on some instance, and for every
+    // constant whether the Shearer check held on all instances; instances
+    // where even the strongest correction is invalid say nothing about it
+    let mut smallest_failed: Option<f64> = None;
+    let mut all_valid = [true; SWEEP_CONSTANTS.len()];
+
+    for ((id, seed), sat) in dataset.rows(seed).zip(dataset.sats()) {
+        eprintln!( "sweep on {} {}/{}", dataset.dataset_name, id + 1, dataset.size() );
+
+        let mat = criterion::default_match(&sat);
+        let results = SWEEP_CONSTANTS.iter().map( |&c| 
+            lll::shearers_bound_checker(&dep::DependencyGraph::from_sat_with_constant(&sat, &mat, c))
+        ).collect::<Vec<_>>();
+
+        let mt = 
+            moser_tardos_algorithm::bench_algorithm::<
+                moser_tardos_algorithm::MTsAlgorithmSimulator<
+                    random_space::InfiniteRandomSpace
+                >
+            >(&sat, turn, random_space::derive_seed(seed, 0));
+
+        let (mean, sigma) = match mt {
+            BenchResult::Failed => { println!("{id},{seed},failed"); continue; }
+            BenchResult::Success { mean, sigma } => { (mean, sigma) }
+        };
+
+        for ((&c, r), valid) in SWEEP_CONSTANTS.iter().zip(results.iter()).zip(all_valid.iter_mut()) {
+            if r.is_invalid() { *valid &= results[0].is_invalid(); continue; }
+            if z_score(r, mean, sigma / sqn) >= z99 && smallest_failed.is_none_or(|s| c < s) {
+                smallest_failed = Some(c);
+            }
+        }
+
+        let bounds = results.iter().map( |r| format!("{r}") ).collect::<Vec<_>>().join(",");
+        println!("{id},{seed},{mean:.3},{sigma:.3},{bounds}");
+    }
+
+    let largest_valid = SWEEP_CONSTANTS.iter().zip(all_valid.iter())
+        .filter( |(_, &v)| v ).map( |(&c, _)| c ).next_back();
+
+    match smallest_failed {
+        None => { eprintln!("smallest failing constant: none"); }
+        Some(c) => { eprintln!("smallest failing constant: {c}"); }
+    }
+    match largest_valid {
+        None => { eprintln!("largest valid constant: none"); }
+        Some(c) => { eprintln!("largest valid constant: {c}"); }
+    }
+}
 
+pub fn optimise_match(dataset: &DataSet) {
+    println!( "id, greedy, best, improvement, matching" );
+
+    for ((id, _), sat) in dataset.rows(DEFAULT_SEED).zip(dataset.sats()) {
+        eprintln!( "optimise on {} {}/{}", dataset.dataset_name, id + 1, dataset.size() );
+
+        let result = match_search::minimise_new_bound(&sat);
+
+        let pairs = result.mat.pairs().iter()
+            .map( |(u, v)| format!("{u}-{v}") )
+            .collect::<Vec<String>>().join(" ");
+
+        println!("{id},{},{},{:.3},{pairs}", result.greedy, result.bound, result.improvement());
+    }
+}
+
+pub fn table(dataset: &DataSet, turn: usize, seed: u64) {
+    // the three selection rules on the same resampling tables
+    eprintln!("seed: {seed}");
+    println!( "id, seed, mt_mean, mt_sigma, new_mean, new_sigma, pair_mean, pair_sigma" );
+
+    type Table = random_space::TableRandomSpace<random_space::InfiniteRandomSpace>;
+
+    for ((id, seed), sat) in dataset.rows(seed).zip(dataset.sats()) {
+        eprintln!( "table on {} {}/{}", dataset.dataset_name, id + 1, dataset.size() );
+
+        let mt = moser_tardos_algorithm::table_algorithm::<
+            moser_tardos_algorithm::MTsAlgorithmSimulator<Table>
+        >(&sat, turn, seed);
+        let pmt = moser_tardos_algorithm::table_algorithm::<
+            moser_tardos_algorithm::NewAlgorithmSimulator<Table>
+        >(&sat, turn, seed);
+        let qmt = moser_tardos_algorithm::table_algorithm::<
+            moser_tardos_algorithm::MatchedAlgorithmSimulator<Table>
+        >(&sat, turn, seed);
+
+        println!("{id},{seed},{mt},{pmt},{qmt}");
     }
 }
 
-pub fn run(dataset: &DataSet) {
+pub fn finite(dataset: &DataSet, turn: usize, seed: u64) {
+    // the CNFs once more as finite-domain instances, both pipelines should
+    // agree up to sampling noise
+    eprintln!("seed: {seed}");
+    println!( "id, seed, SHE, fSHE, mt_mean, mt_sigma, fmt_mean, fmt_sigma, new_mean, new_sigma, fnew_mean, fnew_sigma" );
+
+    for ((id, seed), sat) in dataset.rows(seed).zip(dataset.sats()) {
+        eprintln!( "finite on {} {}/{}", dataset.dataset_name, id + 1, dataset.size() );
+        let instance = domain::FiniteInstance::from_sat(&sat);
+
+        let she = lll::shearers_bound_checker(&dep::DependencyGraph::form_sat(&sat));
+        let fshe = lll::shearers_bound_checker(&instance.dependency_graph());
+
+        let mt = 
+            moser_tardos_algorithm::bench_algorithm::<
+                moser_tardos_algorithm::MTsAlgorithmSimulator<
+                    random_space::InfiniteRandomSpace
+                >
+            >(&sat, turn, random_space::derive_seed(seed, 0));
+        let fmt = 
+            moser_tardos_algorithm::bench_algorithm::<
+                moser_tardos_algorithm::MTsAlgorithmSimulator<
+                    random_space::InfiniteRandomSpace, domain::FiniteInstance
+                >
+            >(&instance, turn, random_space::derive_seed(seed, 0));
+
+        let pmt = 
+            moser_tardos_algorithm::bench_algorithm::<
+                moser_tardos_algorithm::NewAlgorithmSimulator<
+                    random_space::InfiniteRandomSpace
+                >
+            >(&sat, turn, random_space::derive_seed(seed, 1));
+        let fpmt = 
+            moser_tardos_algorithm::bench_algorithm::<
+                moser_tardos_algorithm::NewAlgorithmSimulator<
+                    random_space::InfiniteRandomSpace, domain::FiniteInstance
+                >
+            >(&instance, turn, random_space::derive_seed(seed, 1));
+
+        println!("{id},{seed},{she},{fshe},{mt},{fmt},{pmt},{fpmt}");
+    }
+}
+
+pub fn capture(dataset: &DataSet, turn: usize, factor: f64, seed: u64) {
+    // reruns the MT repetitions of bench and saves the tape of every run
+    // taking at least factor times the mean number of steps
+    eprintln!("seed: {seed}");
+    println!( "id, seed, repetition, steps, mt_mean, tape" );
+
+    std::fs::create_dir_all("./capture").expect("Should have been able to create ./capture");
+
+    type Recorded = moser_tardos_algorithm::MTsAlgorithmSimulator<
+        random_space::RecordingRandomSpace<random_space::InfiniteRandomSpace>
+    >;
+
+    for ((id, seed), sat) in dataset.rows(seed).zip(dataset.sats()) {
+        eprintln!( "capture on {} {}/{}", dataset.dataset_name, id + 1, dataset.size() );
+        let mt_seed = random_space::derive_seed(seed, 0);
+
+        let runs = (0..turn)
+            .map( |i| moser_tardos_algorithm::record_algorithm::<Recorded>(&sat, mt_seed, i) )
+            .collect::<Option<Vec<_>>>();
+        let runs = match runs {
+            None => { println!("{id},{seed},failed"); continue; }
+            Some(runs) => runs
+        };
+        let mean = runs.iter().map( |(step, _)| step ).sum::<usize>() as f64 / turn as f64;
+
+        for (i, (step, tape)) in runs.iter().enumerate() {
+            let step = *step;
+            if (step as f64) < factor * mean || step == 0 { continue; }
+
+            let path = format!("./capture/{}_{id}_{i}.txt", dataset.dataset_name);
+            std::fs::write(&path, format!("{tape:?}")).expect("Should have been able to write the tape");
+
+            println!("{id},{seed},{i},{step},{mean:.3},{path}");
+        }
+    }
+}
+
+fn replay_simulator<A>(name: &str, sat: &sat::SAT, tape: random_space::LimitedRandomSpace)
+    where A: moser_tardos_algorithm::AlgorithmSimulator<random_space::LimitedRandomSpace, Instance = sat::SAT>,
+          A::ResamplingType: std::fmt::Debug
+{
+    use moser_tardos_algorithm::ExecuteResult;
+
+    let mut sand_box = A::new(sat.clone(), tape);
+    sand_box.run_init();
+
+    for step in 0.. {
+        match sand_box.run_next_step() {
+            ExecuteResult::Running { resampled_clause } => { println!("{name},{step},{resampled_clause:?}"); }
+            ExecuteResult::Terminal { .. } => { println!("{name},{step},terminal"); break; }
+            ExecuteResult::RandomSpaceExceed => { println!("{name},{step},exhausted"); break; }
+        }
+    }
+}
+
+pub fn replay(dataset: &DataSet, id: usize, tape_path: &str) {
+    let sat = dataset.sats().nth(id).expect("no such instance");
+    let tape = random_space::LimitedRandomSpace::from_file(tape_path);
+
+    println!( "simulator, step, resampled" );
+
+    replay_simulator::<
+        moser_tardos_algorithm::MTsAlgorithmSimulator<random_space::LimitedRandomSpace>
+    >("MT", &sat, tape.clone());
+    replay_simulator::<
+        moser_tardos_algorithm::NewAlgorithmSimulator<random_space::LimitedRandomSpace>
+    >("New", &sat, tape.clone());
+    replay_simulator::<
+        moser_tardos_algorithm::MatchedAlgorithmSimulator<random_space::LimitedRandomSpace>
+    >("PAIR", &sat, tape);
+}
+
+pub fn run(dataset: &DataSet, seed: u64) {
+    eprintln!("seed: {seed}");
+
+    println!( "id, seed, MT, mt_time, CDCL, cdcl_time" );
+
+    let budget = cdcl::Budget {
+        conflicts: 10000000,
+        time: std::time::Duration::from_secs(300)
+    };
+
     let mut cnt = 0;
-    for (id, sat) in dataset.into_iter().enumerate() {
+    let (mut sat_cnt, mut unsat_cnt, mut unknown_cnt) = (0, 0, 0);
+    for ((id, seed), sat) in dataset.rows(seed).zip(dataset.sats()) {
         eprintln!( "run on {} {}/{}", dataset.dataset_name, id + 1, dataset.size() );
 
+        let start = std::time::Instant::now();
+        let truth = cdcl::solve(&sat, &budget);
+        let cdcl_time = start.elapsed().as_secs_f64();
+
+        match &truth {
+            cdcl::SolveResult::Satisfiable(var) => { 
+                debug_assert!( sat.check_violated(var).iter().all(|&b| !b) );
+                sat_cnt += 1; 
+            }
+            cdcl::SolveResult::Unsatisfiable => { unsat_cnt += 1; }
+            cdcl::SolveResult::Unknown => { unknown_cnt += 1; }
+        }
+
         let dep = dep::DependencyGraph::form_sat(&sat);
 
         match lll::symmertric_lll_checker(&dep) {
-            PredictedResult::Invalid => { continue; }
+            PredictedResult::Invalid(_) => { 
+                println!("{id},{seed},skipped,N/A,{truth},{cdcl_time:.3}");
+                continue; 
+            }
             _ => {}
         }
 
-        if moser_tardos_algorithm::run_algorithm::
+        let start = std::time::Instant::now();
+        let solved = moser_tardos_algorithm::run_algorithm::
             <moser_tardos_algorithm::MTsAlgorithmSimulator
                 <random_space::InfiniteRandomSpace
-            >>(&sat)
-          { cnt += 1; }
+            >>(&sat, random_space::derive_seed(seed, 0));
+        let mt_time = start.elapsed().as_secs_f64();
+
+        if solved { cnt += 1; }
+
+        let mt = if solved { "SAT" } else { "UNKNOWN" };
+        println!("{id},{seed},{mt},{mt_time:.3},{truth},{cdcl_time:.3}");
     }
 
     eprintln!("{cnt}");
-}
\ No newline at end of file
+    eprintln!("cdcl : sat: {sat_cnt} unsat: {unsat_cnt} unknown: {unknown_cnt}");
+}
+
+pub fn generate_hypergraphs(name: &str, count: usize, n: usize, m: usize, k: usize, seed: u64) {
+    // writes ./dataset/{name}/{name}{i}.hg, m random k-edges on n vertices
+    // each, and the list ./dataset/{name}.txt for load_dataset
+    eprintln!("seed: {seed}");
+
+    std::fs::create_dir_all(format!("./dataset/{name}")).expect("Should have been able to create the dataset directory");
+
+    let names = (1..=count).map( |i| {
+        let file = format!("{name}/{name}{i}.hg");
+        hypergraph::Hypergraph::random_uniform(n, m, k, random_space::derive_seed(seed, i as u64))
+            .to_file(&format!("./dataset/{file}"));
+        file
+    } ).collect::<Vec<String>>();
+
+    std::fs::write(format!("./dataset/{name}.txt"), names.join("\n")).expect("Should have been able to write the file");
+}
+
+pub fn colour(dataset: &DataSet, turn: usize, q: usize, data_filter: &str, seed: u64) {
+    // proper vertex colourings with q colours
+    bench_events(dataset, dataset.graphs().map( |g| g.vertex_colouring(q) ), turn, data_filter, seed);
+}
+
+pub fn acyclic(dataset: &DataSet, turn: usize, q: usize, max_len: Option<usize>, data_filter: &str, seed: u64) {
+    // acyclic edge colourings with q colours; with max_len only bichromatic
+    // cycles up to that length are bad events, which is a relaxation
+    match max_len {
+        None => {
+            bench_events(dataset, dataset.graphs().map( |g| g.acyclic_edge_colouring(q) ), turn, data_filter, seed);
+        }
+        Some(max_len) => {
+            eprintln!("relaxed: bichromatic cycles of length at most {max_len} only");
+            bench_events(dataset, dataset.graphs().map( |g| g.acyclic_edge_colouring_up_to(q, max_len) ), turn, data_filter, seed);
+        }
+    }
+}
+
+pub fn generate_graphs(name: &str, count: usize, n: usize, m: usize, seed: u64) {
+    // writes ./dataset/{name}/{name}{i}.col, G(n, m) each, and the list
+    // ./dataset/{name}.txt for load_dataset
+    eprintln!("seed: {seed}");
+
+    std::fs::create_dir_all(format!("./dataset/{name}")).expect("Should have been able to create the dataset directory");
+
+    let names = (1..=count).map( |i| {
+        let file = format!("{name}/{name}{i}.col");
+        graph::Graph::random_gnm(n, m, random_space::derive_seed(seed, i as u64))
+            .to_dimacs(&format!("./dataset/{file}"));
+        file
+    } ).collect::<Vec<String>>();
+
+    std::fs::write(format!("./dataset/{name}.txt"), names.join("\n")).expect("Should have been able to write the file");
+}
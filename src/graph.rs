@@ -0,0 +1,181 @@
+use std::collections::BTreeSet;
+
+use rand::prelude::*;
+
+use crate::domain::{FiniteEvent, FiniteInstance};
+
+// A simple undirected graph, vertices are 0-based and every edge is stored
+// once as (u, v) with u < v.
+#[derive(Clone, Debug)]
+pub struct Graph {
+    n: usize,
+    edge: Vec<(usize, usize)>
+}
+
+impl Graph {
+    pub fn new(n: usize, edge: Vec<(usize, usize)>) -> Self {
+        let mut edge: Vec<(usize, usize)> = edge.into_iter()
+            .filter( |&(u, v)| u != v )
+            .map( |(u, v)| (u.min(v), u.max(v)) )
+            .collect();
+        edge.sort();
+        edge.dedup();
+        debug_assert!( edge.iter().all(|&(_, v)| v < n) );
+        Self { n, edge }
+    }
+
+    // DIMACS graphs end with .col, anything else is read as an edge list
+    pub fn from_file(path: &str) -> Self {
+        if path.ends_with(".col") { Self::from_dimacs(path) } else { Self::from_edge_list(path) }
+    }
+
+    // one "u v" per line with 0-based vertices, lines starting with # or %
+    // are comments
+    pub fn from_edge_list(path: &str) -> Self {
+        let content = std::fs::read_to_string(path)
+            .expect("Should have been able to read the file");
+
+        let edge: Vec<(usize, usize)> = content.split("\n")
+            .map( |line| line.trim() )
+            .filter( |line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('%') )
+            .map( |line| {
+                let x: Vec<usize> = line.split_whitespace().take(2).map( |x| x.parse().unwrap() ).collect();
+                (x[0], x[1])
+            } )
+            .collect();
+
+        let n = edge.iter().map( |&(u, v)| u.max(v) + 1 ).max().unwrap_or(0);
+        Self::new(n, edge)
+    }
+
+    // "p edge <vertices> <edges>" and one "e u v" per edge with 1-based
+    // vertices, lines starting with c are comments
+    pub fn from_dimacs(path: &str) -> Self {
+        let content = std::fs::read_to_string(path)
+            .expect("Should have been able to read the file");
+
+        let mut n = 0;
+        let mut edge = Vec::new();
+        for line in content.split("\n") {
+            let x: Vec<&str> = line.split_whitespace().collect();
+            match x.first() {
+                Some(&"p") => { n = x[2].parse().unwrap(); }
+                Some(&"e") => { edge.push( (x[1].parse::<usize>().unwrap() - 1, x[2].parse::<usize>().unwrap() - 1) ); }
+                _ => {}
+            }
+        }
+
+        Self::new(n, edge)
+    }
+
+    pub fn to_dimacs(&self, path: &str) {
+        let mut content = format!("p edge {} {}\n", self.n, self.edge.len());
+        for &(u, v) in &self.edge {
+            content += &format!("e {} {}\n", u + 1, v + 1);
+        }
+        std::fs::write(path, content).expect("Should have been able to write the file");
+    }
+
+    // G(n, m): m distinct edges chosen uniformly at random
+    pub fn random_gnm(n: usize, m: usize, seed: u64) -> Self {
+        assert!( m <= n * (n - 1) / 2 );
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut edge = BTreeSet::new();
+        while edge.len() < m {
+            let u = rng.gen_range(0..n);
+            let v = rng.gen_range(0..n);
+            if u != v { edge.insert((u.min(v), u.max(v))); }
+        }
+        Self::new(n, edge.into_iter().collect())
+    }
+
+    fn adjacency(&self) -> Vec<Vec<(usize, usize)>> {
+        // (neighbour, edge id)
+        let mut adj = crate::new_vector(self.n, Vec::new());
+        for (id, &(u, v)) in self.edge.iter().enumerate() {
+            adj[u].push((v, id));
+            adj[v].push((u, id));
+        }
+        adj
+    }
+
+    // the edge ids of every simple cycle of even length at most max_len,
+    // each listed once and in order along the cycle; the enumeration walks
+    // every simple path, so it is exponential in max_len on dense graphs
+    pub fn even_cycles(&self, max_len: usize) -> Vec<Vec<usize>> {
+        let adj = self.adjacency();
+        let mut cycles = Vec::new();
+
+        // paths from s through vertices larger than s, a cycle is closed by
+        // an edge back to s and kept in the direction where the second
+        // vertex is smaller than the last one
+        fn dfs(
+            adj: &[Vec<(usize, usize)>],
+            s: usize, x: usize, max_len: usize,
+            path: &mut Vec<usize>, edges: &mut Vec<usize>,
+            cycles: &mut Vec<Vec<usize>>)
+        {
+            for &(y, id) in &adj[x] {
+                if y == s && path.len() >= 4 && path.len().is_multiple_of(2) && path[1] < x {
+                    let mut cycle = edges.clone();
+                    cycle.push(id);
+                    cycles.push(cycle);
+                }
+                if y > s && path.len() < max_len && !path.contains(&y) {
+                    path.push(y);
+                    edges.push(id);
+                    dfs(adj, s, y, max_len, path, edges, cycles);
+                    edges.pop();
+                    path.pop();
+                }
+            }
+        }
+
+        for s in 0..self.n {
+            dfs(&adj, s, s, max_len, &mut vec![s], &mut Vec::new(), &mut cycles);
+        }
+        cycles
+    }
+
+    // a variable per vertex with q uniform colours, a bad event per
+    // monochromatic edge
+    pub fn vertex_colouring(&self, q: usize) -> FiniteInstance {
+        let domain = crate::new_vector(self.n, q);
+        let event = self.edge.iter().map( |&(u, v)|
+            FiniteEvent::from_predicate(vec![u, v], &domain, |t| t[0] == t[1])
+        ).collect();
+        FiniteInstance::new(FiniteInstance::uniform(self.n, q), event)
+    }
+
+    // a variable per edge with q uniform colours; bad events are two
+    // adjacent edges of the same colour and the even cycles coloured
+    // alternately by two colours
+    pub fn acyclic_edge_colouring(&self, q: usize) -> FiniteInstance {
+        self.acyclic_edge_colouring_up_to(q, self.n)
+    }
+
+    // the relaxation of acyclic_edge_colouring that only forbids bichromatic
+    // cycles of length at most max_len, longer ones may remain
+    pub fn acyclic_edge_colouring_up_to(&self, q: usize, max_len: usize) -> FiniteInstance {
+        let m = self.edge.len();
+        let domain = crate::new_vector(m, q);
+
+        let mut event = Vec::new();
+        for incident in self.adjacency() {
+            for (i, &(_, a)) in incident.iter().enumerate() {
+                for &(_, b) in &incident[i + 1..] {
+                    event.push( FiniteEvent::from_predicate(vec![a, b], &domain, |t| t[0] == t[1]) );
+                }
+            }
+        }
+
+        for cycle in self.even_cycles(max_len) {
+            let bad = (0..q).flat_map( |a| (0..q).filter(move |&b| b != a).map(move |b| (a, b)) )
+                .map( |(a, b)| (0..cycle.len()).map(|i| if i.is_multiple_of(2) { a } else { b }).collect() )
+                .collect();
+            event.push( FiniteEvent::new(cycle, bad) );
+        }
+
+        FiniteInstance::new(FiniteInstance::uniform(m, q), event)
+    }
+}
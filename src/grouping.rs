@@ -0,0 +1,90 @@
+use core::slice;
+
+use crate::sat::SAT;
+use crate::r#match::{self, Match};
+
+// disjoint groups of clauses, the generalisation of `Match` to more than two
+pub struct Grouping {
+    group: Vec<Vec<usize>>
+}
+
+impl Grouping {
+    pub fn from_match(mat: &Match) -> Self {
+        Self { group: mat.into_iter().map(|&(u, v)| vec![u, v]).collect() }
+    }
+
+    pub fn from_sat_greedy(sat: &SAT, k: usize) -> Self {
+        // grow a group from every clause not grouped yet, in index order,
+        // adding the neighbour that keeps pr_land of the whole group largest,
+        // up to k clauses
+        let n = sat.size();
+        let occur = r#match::occurrence_index(sat);
+        let mut choosed = crate::new_vector(n, false);
+        let mut group = Vec::new();
+
+        for u in 0..n { if !choosed[u] {
+            let mut g = vec![u];
+            choosed[u] = true;
+
+            while g.len() < k {
+                let mut candidate = None;
+                let mut mx = 0.0;
+
+                for &w in g.iter() {
+                    for x in sat.get_clause(w).collect_varible() {
+                        for &v in &occur[x - 1] {
+                            if choosed[v] || r#match::pairwise_weight(sat, w, v) == 0.0 { continue; }
+                            let pr = sat.pr_land_exact(&[&g[..], &[v]].concat());
+                            if mx < pr || (mx == pr && candidate.is_some_and(|c| v < c)) {
+                                mx = pr;
+                                candidate = Some(v);
+                            }
+                        }
+                    }
+                }
+
+                match candidate {
+                    None => { break; }
+                    Some(v) => { g.push(v); choosed[v] = true; }
+                }
+            }
+
+            if g.len() > 1 { group.push(g); }
+            else { choosed[u] = false; }
+        }}
+
+        Self { group }
+    }
+
+    // for every member of the group, sum of (-1)^|S| pr_land(S)^2 over the
+    // subsets S of the group containing it with |S| >= 2, leaving out the
+    // subsets whose clauses are independent of each other
+    pub fn correction(sat: &SAT, g: &[usize]) -> Vec<f64> {
+        let mut ret = crate::new_vector(g.len(), 0.0);
+
+        for mask in 1usize..1 << g.len() {
+            if mask.count_ones() < 2 { continue; }
+
+            let s: Vec<usize> = (0..g.len()).filter(|&i| mask >> i & 1 == 1).map(|i| g[i]).collect();
+            let pr = sat.pr_land_exact(&s);
+            let independent: f64 = s.iter().map(|&c| sat.pr_land_exact(&[c])).product();
+            if pr == independent { continue; }
+
+            let term = if mask.count_ones() % 2 == 0 { pr * pr } else { -pr * pr };
+            for i in (0..g.len()).filter(|&i| mask >> i & 1 == 1) {
+                ret[i] += term;
+            }
+        }
+
+        ret
+    }
+}
+
+impl<'a> IntoIterator for &'a Grouping {
+    type Item = &'a Vec<usize>;
+    type IntoIter = slice::Iter<'a, Vec<usize>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.group.iter()
+    }
+}
@@ -0,0 +1,145 @@
+use rand::prelude::*;
+
+use crate::sat::SAT;
+use crate::event::{BadEvent, Instance};
+use crate::random_space::RandomSpace;
+
+// Property B: colour the vertices with two colours so that no edge is
+// monochromatic. The colour of a vertex is a boolean variable, vertices
+// are 0-based.
+#[derive(Clone, Debug)]
+pub struct HyperEdge {
+    vertex: Vec<usize>
+}
+
+#[derive(Clone, Debug)]
+pub struct Hypergraph {
+    n: usize,              // number of vertices
+    edge: Vec<HyperEdge>,
+    bias: Vec<f64>         // P(colour of v = 1)
+}
+
+impl HyperEdge {
+    pub fn new(mut vertex: Vec<usize>) -> Self {
+        debug_assert!( !vertex.is_empty() );
+        vertex.sort();
+        vertex.dedup();
+        Self { vertex }
+    }
+}
+
+impl BadEvent for HyperEdge {
+    type Value = bool;
+    type Distribution = f64;
+
+    fn variables(&self) -> Vec<usize> {
+        self.vertex.clone()
+    }
+
+    fn check_violated(&self, value: &[bool]) -> bool {
+        let first = value[self.vertex[0]];
+        self.vertex.iter().all(|&v| value[v] == first)
+    }
+
+    fn probability(&self, distribution: &[f64]) -> f64 {
+        // all ones or all zeros
+        let one: f64 = self.vertex.iter().map(|&v| distribution[v]).product();
+        let zero: f64 = self.vertex.iter().map(|&v| 1.0 - distribution[v]).product();
+        one + zero
+    }
+}
+
+impl Hypergraph {
+    pub fn new(n: usize, edge: Vec<Vec<usize>>) -> Self {
+        debug_assert!( edge.iter().flatten().all(|&v| v < n) );
+        Self {
+            n,
+            edge: edge.into_iter().map(HyperEdge::new).collect(),
+            bias: crate::new_vector(n, 0.5)
+        }
+    }
+
+    // "p hg <vertices> <edges>", then one edge per line as its 1-based
+    // vertices ending with 0, like the clauses of a DIMACS CNF
+    pub fn from_file(path: &str) -> Self {
+        let content = std::fs::read_to_string(path)
+            .expect("Should have been able to read the file");
+
+        let mut lines = content.split("\n").filter(|&line| { !line.is_empty() && !line.starts_with('c') });
+
+        let header: Vec<&str> = lines.next().expect("Should have a header").split_whitespace().collect();
+        assert!( header.len() == 4 && header[0] == "p" && header[1] == "hg", "Should start with p hg <vertices> <edges>" );
+        let n: usize = header[2].parse().unwrap();
+
+        let edge: Vec<Vec<usize>> = lines.map( |line| {
+                line.split_whitespace()
+                    .map( |x| x.parse::<usize>().unwrap() )
+                    .filter( |&x| x != 0 )
+                    .map( |x| x - 1 )
+                    .collect()
+            } ).collect();
+        debug_assert_eq!( edge.len(), header[3].parse::<usize>().unwrap() );
+
+        Self::new(n, edge)
+    }
+
+    pub fn to_file(&self, path: &str) {
+        let mut content = format!("p hg {} {}\n", self.n, self.edge.len());
+        for e in &self.edge {
+            for v in &e.vertex { content += &format!("{} ", v + 1); }
+            content += "0\n";
+        }
+        std::fs::write(path, content).expect("Should have been able to write the file");
+    }
+
+    // m edges, each a uniformly random k-subset of the n vertices
+    pub fn random_uniform(n: usize, m: usize, k: usize, seed: u64) -> Self {
+        assert!( k <= n );
+        let mut rng = StdRng::seed_from_u64(seed);
+        let edge = (0..m).map( |_| rand::seq::index::sample(&mut rng, n, k).into_vec() ).collect();
+        Self::new(n, edge)
+    }
+
+    pub fn with_bias(mut self, bias: Vec<f64>) -> Self {
+        assert_eq!( bias.len(), self.n );
+        assert!( bias.iter().all(|q| (0.0..=1.0).contains(q)) );
+        self.bias = bias;
+        self
+    }
+
+    // every edge as the two clauses "all true" and "all false", a 2-colouring
+    // is proper if and only if it satisfies the CNF
+    pub fn to_sat(&self) -> SAT {
+        let clause: Vec<Vec<isize>> = self.edge.iter().flat_map( |e| {
+            let pos: Vec<isize> = e.vertex.iter().map(|&v| v as isize + 1).collect();
+            let neg: Vec<isize> = pos.iter().map(|&l| -l).collect();
+            [pos, neg]
+        } ).collect();
+
+        let sat = SAT::from_vec(clause);
+        let m = sat.variable_count();
+        sat.with_bias(self.bias[..m].to_vec())
+    }
+}
+
+impl Instance for Hypergraph {
+    type Event = HyperEdge;
+
+    fn events(&self) -> &[HyperEdge] {
+        &self.edge
+    }
+
+    fn distribution(&self) -> &[f64] {
+        &self.bias
+    }
+
+    fn sample<R: RandomSpace>(&self, v: usize, random_space: &mut R) -> Option<bool> {
+        random_space.fetch_biased_bit(v + 1, self.bias[v])
+    }
+
+    fn log2_density(&self) -> Option<f64> {
+        // vertices in no edge do not matter
+        if self.edge.is_empty() { return Some(0.0); }
+        self.to_sat().log2_density()
+    }
+}
@@ -1,50 +1,126 @@
 use crate::dep::DependencyGraph;
-use crate::sat::SAT;
+use crate::event::Instance;
 
 const E : f64 = std::f64::consts::E;
 
-#[derive(Debug)]
+// relative tolerance used when comparing two predictions
+const TOLERANCE: f64 = 1e-9;
+
+// largest number of events shearers_bound_checker enumerates the subsets of
+pub const SHEARER_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidReason {
+    TooLarge,
+    CriterionViolated,
+    Unsatisfiable,
+    NumericalFailure,
+}
+
+#[derive(Debug, Clone, Copy)]
 pub enum PredictedResult {
-    Invalid,
+    Invalid(InvalidReason),
     UpperBound(f64),
+    Estimate {
+        value: f64,
+        lower: f64,
+        upper: f64
+    },
+}
+
+impl std::fmt::Display for InvalidReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
 }
 
 impl std::fmt::Display for PredictedResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            &Self::Invalid => { write!(f, "N/A") }
+            &Self::Invalid(reason) => { write!(f, "N/A:{}", reason) }
             &Self::UpperBound(up) => {
                 write!(f, "{:.3}", up)
             }
+            &Self::Estimate { value, lower, upper } => {
+                write!(f, "{:.3}[{:.3};{:.3}]", value, lower, upper)
+            }
         }
     }
 }
 
-impl PartialEq for PredictedResult {
-    fn eq(&self, other: &Self) -> bool {
+fn approx_eq_f64(a: f64, b: f64, tolerance: f64) -> bool {
+    a == b || (a - b).abs() <= tolerance * a.abs().max(b.abs()).max(1.0)
+}
+
+impl PredictedResult {
+    pub fn is_invalid(&self) -> bool {
+        matches!(self, Self::Invalid(_))
+    }
+
+    // upper bounds with non-finite values are numerical failures, not bounds
+    fn checked(self) -> Self {
+        match self {
+            Self::UpperBound(up) if !up.is_finite() => { Self::Invalid(InvalidReason::NumericalFailure) }
+            _ => { self }
+        }
+    }
+
+    pub fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
         match (self, other) {
-            (PredictedResult::Invalid, PredictedResult::Invalid) => {true},
-            _ => {false}
+            (Self::Invalid(a), Self::Invalid(b)) => { a == b }
+            (Self::UpperBound(a), Self::UpperBound(b)) => { approx_eq_f64(*a, *b, tolerance) }
+            (Self::Estimate { value: a, lower: la, upper: ua },
+             Self::Estimate { value: b, lower: lb, upper: ub }) => {
+                approx_eq_f64(*a, *b, tolerance) 
+                && approx_eq_f64(*la, *lb, tolerance) 
+                && approx_eq_f64(*ua, *ub, tolerance)
+            }
+            _ => { false }
         }
     }
 }
 
+impl PartialEq for PredictedResult {
+    fn eq(&self, other: &Self) -> bool {
+        self.approx_eq(other, TOLERANCE)
+    }
+}
+
+pub struct TimedResult {
+    pub result: PredictedResult,
+    pub runtime: std::time::Duration
+}
+
+pub fn timed<F>(checker: F) -> TimedResult
+    where F: FnOnce() -> PredictedResult
+{
+    let start = std::time::Instant::now();
+    let result = checker();
+    TimedResult { result, runtime: start.elapsed() }
+}
+
+impl std::fmt::Display for TimedResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.result)
+    }
+}
+
 pub fn symmertric_lll_checker(dep: &DependencyGraph) -> PredictedResult {
     if E * dep.max_p * (dep.max_d as f64) < 1.0 {
         let a = dep.max_p * (1.0 + 1.0 / dep.max_d as f64).powi(dep.max_d as i32);
         PredictedResult::UpperBound( 
             (dep.n as f64 / dep.max_d as f64)
             .min(dep.n as f64 * a / (1.0 - a)) 
-        )
+        ).checked()
     } else {
-        PredictedResult::Invalid
+        PredictedResult::Invalid(InvalidReason::CriterionViolated)
     }
 }
 
 pub fn shearers_bound_checker(dep: &DependencyGraph) -> PredictedResult {
     // O(2^n * n) by using fmt
 
-    if dep.n > 20 { return PredictedResult::Invalid; }
+    if dep.n > SHEARER_LIMIT { return PredictedResult::Invalid(InvalidReason::TooLarge); }
 
     let n = dep.n;
 
@@ -83,29 +159,211 @@ pub fn shearers_bound_checker(dep: &DependencyGraph) -> PredictedResult {
     }
 
     if crate::min_f64(&q) < 0.0 {
-        PredictedResult::Invalid
+        PredictedResult::Invalid(InvalidReason::CriterionViolated)
+    } else if q[0] <= 0.0 {
+        PredictedResult::Invalid(InvalidReason::NumericalFailure)
     } else {
         PredictedResult::UpperBound(
             (0..n).map(|i| q[1 << i] ).sum::<f64>() / q[0]
-        )
+        ).checked()
+    }
+}
+
+pub fn satisfiability_checker<I: Instance>(instance: &I, seed: u64) -> PredictedResult {
+    // 1 / P(no bad event), the expected number of trials of rejection
+    // sampling; 2^m / #solutions for fair coins
+
+    match instance.log2_density() {
+        None => { estimated_satisfiability_checker(instance, 10000, seed) }
+        Some(density) if density == f64::NEG_INFINITY => { PredictedResult::Invalid(InvalidReason::Unsatisfiable) }
+        Some(density) => { PredictedResult::UpperBound( (-density).exp2() ).checked() }
+    }
+}
+
+pub fn estimated_satisfiability_checker<I: Instance>(instance: &I, samples: usize, seed: u64) -> PredictedResult {
+    use crate::random_space::InfiniteRandomSpace;
+
+    let density = instance.estimate_density(samples, &mut InfiniteRandomSpace::new(seed as usize));
+
+    // no sample survived, the density is below what the estimator can resolve
+    if density.mean == 0.0 
+      { PredictedResult::Invalid(InvalidReason::NumericalFailure) }
+    else {
+        PredictedResult::Estimate { 
+            value: 1.0 / density.mean, 
+            lower: 1.0 / density.upper, 
+            upper: 1.0 / density.lower
+        }
+    }
+}
+
+pub fn asymmetric_lll_weights(dep: &DependencyGraph, scale: f64) -> Option<Vec<f64>> {
+    // smallest x with x_i = scale * p_i / prod_{j in gamma(i)} (1 - x_j),
+    // found by monotone fixed point iteration from x = 0
+
+    let mut x = crate::new_vector(dep.n, 0.0);
+
+    for _ in 0..10000 {
+        let mut delta: f64 = 0.0;
+        let mut next = Vec::with_capacity(dep.n);
+
+        for i in 0..dep.n {
+            let rest: f64 = dep.get_gamma(i).iter().map(|&j| 1.0 - x[j]).product();
+            let xi = scale * dep.get_p(i) / rest;
+            if xi.is_nan() || xi >= 1.0 { return None; }
+            delta = delta.max(xi - x[i]);
+            next.push(xi);
+        }
+
+        x = next;
+        if delta < 1e-12 { return Some(x); }
+    }
+
+    None
+}
+
+pub fn asymmetric_lll_checker(dep: &DependencyGraph) -> PredictedResult {
+    match asymmetric_lll_weights(dep, 1.0) {
+        None => PredictedResult::Invalid(InvalidReason::CriterionViolated),
+        Some(x) => PredictedResult::UpperBound(
+            x.iter().map(|xi| xi / (1.0 - xi)).sum()
+        ).checked()
+    }
+}
+
+pub fn independent_set_polynomial(dep: &DependencyGraph) -> Option<Vec<f64>> {
+    // c[k] = sum of prod p_i over independent sets of size k,
+    // so that q_empty(lambda * p) = sum_k c[k] * (-lambda)^k
+
+    if dep.n > 20 { return None; }
+
+    let mut c = crate::new_vector(dep.n + 1, 0.0);
+    let mut vis = crate::new_vector(dep.n, 0usize);
+
+    fn dfs(
+        c: &mut Vec<f64>,
+        vis: &mut Vec<usize>,
+        dep: &DependencyGraph,
+        size: usize, pt: usize, pre: f64)
+    {
+        c[size] += pre;
+
+        for npt in pt..dep.n { if vis[npt] == 0 {
+            for d in dep.get_gamma_plus(npt)
+              { vis[d] += 1; }
+
+            dfs( c, vis, dep, size + 1, npt + 1, pre * dep.get_p(npt) );
+
+            for d in dep.get_gamma_plus(npt)
+              { vis[d] -= 1; }
+        } }
+    }
+
+    dfs(&mut c, &mut vis, dep, 0, 0, 1.0);
+
+    Some(c)
+}
+
+fn log_tail(scale: f64, t: usize, log_weight: f64) -> f64 {
+    // ln( scale^{-t} * (e^{log_weight} - 1) ), the total weight of the stable set
+    // sequences of size t under p, given that scale * p has total weight e^{log_weight} - 1
+    log_weight + (-(-log_weight).exp_m1()).ln() - t as f64 * scale.ln()
+}
+
+fn markov_tail(expected: &PredictedResult, t: usize) -> f64 {
+    match expected {
+        PredictedResult::UpperBound(e) if t > 0 => { (e / t as f64).min(1.0) }
+        _ => { 1.0 }
     }
 }
 
-pub fn satisfiability_checker(sat: &SAT) -> PredictedResult {
-    use crate::random_space::random_space_of_nbits;
+pub fn symmertric_lll_tail_bound(dep: &DependencyGraph, t: usize) -> PredictedResult {
+    // P(MT runs >= t steps) <= scale^{-t} * ((1 - x)^{-n} - 1)
+    // whenever scale * p <= x * (1 - x)^d
+
+    let expected = symmertric_lll_checker(dep);
+    if expected.is_invalid() { return expected; }
+
+    if t == 0 { return PredictedResult::UpperBound(1.0); }
+    if dep.max_p == 0.0 { return PredictedResult::UpperBound(0.0); }
+
+    let d = dep.max_d as f64;
+    let n = dep.n as f64;
+
+    let best = (1..1000).map(|i| i as f64 / 1000.0)
+        .filter_map(|x| {
+            let scale = x * (1.0 - x).powf(d) / dep.max_p;
+            if scale <= 1.0 { None } 
+            else { Some(log_tail(scale, t, -n * (1.0 - x).ln())) }
+        })
+        .fold(f64::INFINITY, f64::min);
+
+    PredictedResult::UpperBound( best.exp().min(markov_tail(&expected, t)) ).checked()
+}
+
+pub fn asymmetric_lll_tail_bound(dep: &DependencyGraph, t: usize) -> PredictedResult {
+    // P(MT runs >= t steps) <= scale^{-t} * (prod (1 - x_i)^{-1} - 1)
+    // whenever x satisfies the asymmetric criterion for scale * p
+
+    let expected = asymmetric_lll_checker(dep);
+    if expected.is_invalid() { return expected; }
+
+    if t == 0 { return PredictedResult::UpperBound(1.0); }
+    if crate::max_f64(&dep.p) == 0.0 { return PredictedResult::UpperBound(0.0); }
 
-    let m = sat.variable_count();
+    // largest feasible scale by bisection
+    let (mut lo, mut hi) = (1.0, 2.0);
+    while asymmetric_lll_weights(dep, hi).is_some() { lo = hi; hi *= 2.0; }
+    for _ in 0..50 {
+        let mid = (lo + hi) / 2.0;
+        if asymmetric_lll_weights(dep, mid).is_some() { lo = mid; } else { hi = mid; }
+    }
+
+    let best = (1..=64).map(|i| lo.powf(i as f64 / 64.0))
+        .filter_map(|scale| {
+            let x = asymmetric_lll_weights(dep, scale)?;
+            Some(log_tail(scale, t, -x.iter().map(|xi| (1.0 - xi).ln()).sum::<f64>()))
+        })
+        .fold(f64::INFINITY, f64::min);
+
+    PredictedResult::UpperBound( best.exp().min(markov_tail(&expected, t)) ).checked()
+}
+
+pub fn shearers_tail_bound(dep: &DependencyGraph, t: usize) -> PredictedResult {
+    // Kolipaka-Szegedy: P(MT runs >= t steps) <= scale^{-t} * (1 / q_empty(scale * p) - 1)
+    // for every scale such that scale * p is still in the Shearer region,
+    // i.e. q_empty(mu * p) > 0 for all mu in [0, scale]
+
+    let c = match independent_set_polynomial(dep) {
+        None => { return PredictedResult::Invalid(InvalidReason::TooLarge); }
+        Some(c) => c
+    };
+
+    let q = |scale: f64| -> f64 {
+        c.iter().enumerate().map(|(k, ck)| ck * (-scale).powi(k as i32)).sum()
+    };
+
+    if q(1.0) <= 0.0 { return PredictedResult::Invalid(InvalidReason::CriterionViolated); }
+
+    let expected = shearers_bound_checker(dep);
+    if expected.is_invalid() { return expected; }
+
+    if t == 0 { return PredictedResult::UpperBound(1.0); }
+    if c.iter().skip(1).all(|&ck| ck == 0.0) { return PredictedResult::UpperBound(0.0); }
+
+    // first sign change of q_empty along the ray on a 0.1% grid, then
+    // bisection; a dip below zero narrower than one grid step is not seen
+    let (mut lo, mut hi) = (1.0, 1.001);
+    while q(hi) > 0.0 { lo = hi; hi *= 1.001; }
+    for _ in 0..50 {
+        let mid = (lo + hi) / 2.0;
+        if q(mid) > 0.0 { lo = mid; } else { hi = mid; }
+    }
 
-    if m > 20 { return PredictedResult::Invalid; }
+    let best = (1..=256).map(|i| lo.powf(i as f64 / 256.0))
+        .filter(|&scale| q(scale) > 0.0)
+        .map(|scale| log_tail(scale, t, -q(scale).ln()))
+        .fold(f64::INFINITY, f64::min);
 
-    let solution: usize = random_space_of_nbits(m)
-        .map(|r| {
-            let violated = sat.check_violated(&r.collect());
-            ( violated.iter().map(|x| *x as usize).sum::<usize>() == 0 ) as usize
-    } ).sum();
-    
-    if solution == 0
-      { PredictedResult::Invalid }
-    else 
-      { PredictedResult::UpperBound( (1 << m) as f64 / solution as f64 ) }
+    PredictedResult::UpperBound( best.exp().min(markov_tail(&expected, t)) ).checked()
 }
@@ -5,7 +5,18 @@ use utility::*;
 mod sat;
 mod dep;
 mod lll;
+mod counting;
+mod cdcl;
+mod density;
+mod criterion;
 mod r#match;
+mod blossom;
+mod match_search;
+mod grouping;
+mod event;
+mod domain;
+mod hypergraph;
+mod graph;
 mod random_space;
 mod moser_tardos_algorithm;
 
@@ -17,17 +28,60 @@ mod tests;
 fn main() {
     let args: Vec<String> = std::env::args().collect();
 
-    let dataset = exp::load_dataset( &args[1] );
+    // flags may come in any order after the command, the rest is positional
+    let mut data_filter = "";
+    let mut seed = exp::DEFAULT_SEED;
+    let mut bias = None;
+    let mut row = None;
+    let mut row_seed = None;
+    let mut positional = Vec::new();
+    for arg in &args[3..] {
+        if let Some(filter) = arg.strip_prefix("--filter=") 
+          { data_filter = filter; }
+        else if let Some(s) = arg.strip_prefix("--seed=") 
+          { seed = s.parse().expect("--seed should be an unsigned integer"); }
+        else if let Some(q) = arg.strip_prefix("--bias=") 
+          { bias = Some(q.parse().expect("--bias should be a probability")); }
+        else if let Some(id) = arg.strip_prefix("--id=") 
+          { row = Some(id.parse().expect("--id should be an instance id")); }
+        else if let Some(s) = arg.strip_prefix("--row-seed=") 
+          { row_seed = Some(s.parse().expect("--row-seed should be an unsigned integer")); }
+        else 
+          { positional.push(arg.as_str()); }
+    }
 
-    let (turn_id, data_filter) = 
-    if args.len() > 3 && args[3].starts_with("--filter=") 
-      { (4, &args[3][9..]) } 
-    else { (3, "") };
+    // generators write the dataset instead of reading it
+    if args[2] == "hypergraph" {
+        let p: Vec<usize> = positional.iter().map( |x| x.parse().unwrap() ).collect();
+        exp::generate_hypergraphs(&args[1], p[0], p[1], p[2], p[3], seed);
+        return;
+    }
+    if args[2] == "graph" {
+        let p: Vec<usize> = positional.iter().map( |x| x.parse().unwrap() ).collect();
+        exp::generate_graphs(&args[1], p[0], p[1], p[2], seed);
+        return;
+    }
+
+    let mut dataset = exp::load_dataset( &args[1] );
+    if let Some(q) = bias 
+      { dataset = dataset.with_bias(q); }
+    // a single row, under the seed printed in it if given
+    assert!( row_seed.is_none() || row.is_some(), "--row-seed needs --id" );
+    if let Some(id) = row 
+      { dataset = dataset.with_row(id, row_seed); }
 
     match args[2].as_str() {
-        "bench" => { exp::bench     (&dataset, args[turn_id].parse().unwrap(), data_filter); },
-        "enum"  => { exp::enum_step (&dataset, args[turn_id].parse().unwrap(), data_filter); }
-        "run"  => { exp::run(&dataset); }
+        "bench" => { exp::bench     (&dataset, positional[0].parse().unwrap(), data_filter, seed); },
+        "enum"  => { exp::enum_step (&dataset, positional[0].parse().unwrap(), data_filter, seed); }
+        "sweep" => { exp::sweep     (&dataset, positional[0].parse().unwrap(), seed); }
+        "table" => { exp::table     (&dataset, positional[0].parse().unwrap(), seed); }
+        "colour" => { exp::colour   (&dataset, positional[0].parse().unwrap(), positional[1].parse().unwrap(), data_filter, seed); }
+        "acyclic" => { exp::acyclic (&dataset, positional[0].parse().unwrap(), positional[1].parse().unwrap(), positional.get(2).map( |x| x.parse().unwrap() ), data_filter, seed); }
+        "finite" => { exp::finite   (&dataset, positional[0].parse().unwrap(), seed); }
+        "capture" => { exp::capture (&dataset, positional[0].parse().unwrap(), positional[1].parse().unwrap(), seed); }
+        "replay" => { exp::replay   (&dataset, positional[0].parse().unwrap(), positional[1]); }
+        "run"  => { exp::run(&dataset, seed); }
+        "optimise" => { exp::optimise_match(&dataset); }
         _ => {}
     };
 }
@@ -2,26 +2,68 @@ use core::slice;
 
 use crate::sat::SAT;
 
+// pr_land(u, v)^2, the size of the correction a matched pair receives;
+// pairs without a common variable get no correction
+pub fn pairwise_weight(sat: &SAT, u: usize, v: usize) -> f64 {
+    let pr = sat.pr_land_pair(u, v);
+    let independent = sat.pr_land_pair(u, u) * sat.pr_land_pair(v, v);
+    if pr > independent { pr * pr } else { 0.0 }
+}
+
+// clauses containing each variable, indexed by variable - 1
+pub fn occurrence_index(sat: &SAT) -> Vec<Vec<usize>> {
+    let mut occur = crate::new_vector(sat.variable_count(), Vec::new());
+    for (id, c) in sat.into_iter().enumerate() {
+        for v in c.collect_varible() {
+            if occur[v - 1].last() != Some(&id) { occur[v - 1].push(id); }
+        }
+    }
+    occur
+}
+
+// for every clause u, the clauses v > u sharing a variable with it
+fn later_neighbors(sat: &SAT, occur: &[Vec<usize>], u: usize, mark: &mut [usize]) -> Vec<usize> {
+    let mut ret = Vec::new();
+    for x in sat.get_clause(u).collect_varible() {
+        for &v in &occur[x - 1] {
+            if v > u && mark[v] != u + 1 {
+                mark[v] = u + 1;
+                ret.push(v);
+            }
+        }
+    }
+    ret.sort();
+    ret
+}
+
+// pairs sharing a variable with a positive correction, each once
+pub fn candidate_pairs(sat: &SAT) -> Vec<(usize, usize, f64)> {
+    let occur = occurrence_index(sat);
+    let mut mark = crate::new_vector(sat.size(), 0);
+    let mut ret = Vec::new();
+
+    for u in 0..sat.size() {
+        for v in later_neighbors(sat, &occur, u, &mut mark) {
+            let w = pairwise_weight(sat, u, v);
+            if w > 0.0 { ret.push((u, v, w)); }
+        }
+    }
+
+    ret
+}
+
+#[derive(Clone, Debug)]
 pub struct Match {
     edge: Vec<(usize, usize)>
 }
 
 impl Match {
-    pub fn from_random(n: usize) -> Self {
-        use rand::prelude::*;
+    pub fn from_pairs(edge: Vec<(usize, usize)>) -> Self {
+        Self { edge }
+    }
 
-        let mut rng = rand::thread_rng();
-        let mut nums: Vec<usize> = (0..n).collect();
-        nums.shuffle(&mut rng);
-        
-        let mut edge = Vec::new();
-        for i in 0..n/2 {
-            edge.push((nums[i * 2], nums[i * 2 + 1]));
-        }
-        
-        Self {
-            edge
-        }
+    pub fn pairs(&self) -> &Vec<(usize, usize)> {
+        &self.edge
     }
 
     pub fn from_sat_greedy(sat: &SAT) -> Self {
@@ -54,6 +96,66 @@ impl Match {
 
         Self{ edge }
     }
+
+    pub fn from_sat_indexed(sat: &SAT) -> Self {
+        // same rule as from_sat_greedy, but candidates come from the variable
+        // occurrence index, pairs without a common variable are never corrected
+        let n = sat.size();
+        let occur = occurrence_index(sat);
+        let mut mark = crate::new_vector(n, 0);
+        let mut choosed = crate::new_vector(n, false);
+        let mut edge = Vec::new();
+
+        for u in 0..n { if !choosed[u] {
+            let mut candidate = None;
+            let mut mx = 0.0;
+
+            for v in later_neighbors(sat, &occur, u, &mut mark) {
+                if choosed[v] || pairwise_weight(sat, u, v) == 0.0 { continue; }
+                let pairwise_pr = sat.pr_land_pair(u, v);
+                if mx < pairwise_pr {
+                    mx = pairwise_pr;
+                    candidate = Some(v);
+                }
+            }
+
+            if let Some(v) = candidate {
+                edge.push((u, v));
+                choosed[u] = true;
+                choosed[v] = true;
+            }
+        }}
+
+        Self{ edge }
+    }
+
+    pub fn from_sat_max_weight(sat: &SAT) -> Self {
+        // maximise the total correction over matched pairs
+        let n = sat.size();
+        let weight = candidate_pairs(sat);
+
+        if weight.is_empty() { return Self { edge: Vec::new() }; }
+
+        // integral weights for the blossom algorithm, relative precision 2^-40
+        let mx = weight.iter().map(|e| e.2).fold(0.0, f64::max);
+        let edges: Vec<(usize, usize, i64)> = weight.into_iter()
+            .map(|(u, v, w)| (u, v, (w / mx * (1u64 << 40) as f64).round() as i64))
+            .filter(|e| e.2 > 0)
+            .collect();
+
+        let mate = crate::blossom::max_weight_matching(n, &edges);
+
+        Self {
+            edge: (0..n).filter_map(|u| match mate[u] {
+                Some(v) if u < v => Some((u, v)),
+                _ => None
+            }).collect()
+        }
+    }
+
+    pub fn total_weight(&self, sat: &SAT) -> f64 {
+        self.edge.iter().map(|&(u, v)| pairwise_weight(sat, u, v)).sum()
+    }
 }
 
 impl IntoIterator for Match {
@@ -0,0 +1,144 @@
+use crate::sat::SAT;
+use crate::dep::DependencyGraph;
+use crate::lll::{self, InvalidReason, PredictedResult};
+use crate::r#match::Match;
+
+// exhaustive search is (n - 1)!! shearer evaluations
+const EXHAUSTIVE_LIMIT: usize = 10;
+
+pub struct SearchResult {
+    pub mat: Match,
+    pub bound: PredictedResult,
+    pub greedy: PredictedResult,
+}
+
+impl SearchResult {
+    // how much lower the best bound is than the greedy one
+    pub fn improvement(&self) -> f64 {
+        match (self.greedy, self.bound) {
+            (PredictedResult::UpperBound(g), PredictedResult::UpperBound(b)) => { g - b }
+            (PredictedResult::Invalid(_), PredictedResult::UpperBound(_)) => { f64::INFINITY }
+            _ => { f64::NAN }
+        }
+    }
+}
+
+fn new_bound(sat: &SAT, pairs: &[(usize, usize)]) -> PredictedResult {
+    let mat = Match::from_pairs(pairs.to_vec());
+    lll::shearers_bound_checker(&DependencyGraph::from_sat_with_match(sat, &mat))
+}
+
+fn objective(result: &PredictedResult) -> f64 {
+    match *result {
+        PredictedResult::UpperBound(up) => { up }
+        _ => { f64::INFINITY }
+    }
+}
+
+fn exhaustive(sat: &SAT) -> Vec<(usize, usize)> {
+    // every perfect matching, or near perfect one when n is odd
+    fn dfs(
+        sat: &SAT,
+        used: &mut Vec<bool>,
+        pairs: &mut Vec<(usize, usize)>,
+        single: bool,
+        best: &mut (f64, Vec<(usize, usize)>))
+    {
+        let u = match (0..used.len()).find(|&u| !used[u]) {
+            None => {
+                let value = objective(&new_bound(sat, pairs));
+                if value < best.0 { *best = (value, pairs.clone()); }
+                return;
+            }
+            Some(u) => u
+        };
+
+        used[u] = true;
+        if single { dfs(sat, used, pairs, false, best); }
+        for v in u + 1..used.len() { if !used[v] {
+            used[v] = true;
+            pairs.push((u, v));
+            dfs(sat, used, pairs, single, best);
+            pairs.pop();
+            used[v] = false;
+        } }
+        used[u] = false;
+    }
+
+    let n = sat.size();
+    let mut best = (f64::INFINITY, Vec::new());
+    dfs(sat, &mut crate::new_vector(n, false), &mut Vec::new(), n % 2 == 1, &mut best);
+    best.1
+}
+
+fn local_search(sat: &SAT, start: &Match) -> Vec<(usize, usize)> {
+    let n = sat.size();
+    let mut pairs = start.pairs().clone();
+
+    // extra pairs never increase the bound, so complete the start to a (near) perfect matching
+    let mut used = crate::new_vector(n, false);
+    for &(u, v) in &pairs { used[u] = true; used[v] = true; }
+    let mut free: Vec<usize> = (0..n).filter(|&u| !used[u]).collect();
+    while free.len() >= 2 {
+        let v = free.pop().unwrap();
+        let u = free.pop().unwrap();
+        pairs.push((u, v));
+    }
+    let mut single = free.pop();
+
+    let mut best = objective(&new_bound(sat, &pairs));
+
+    for _ in 0..100 {
+        let mut improved = false;
+
+        for i in 0..pairs.len() {
+            for j in i + 1..pairs.len() {
+                let ((a, b), (c, d)) = (pairs[i], pairs[j]);
+                for (x, y) in [((a, c), (b, d)), ((a, d), (b, c))] {
+                    let (old_x, old_y) = (pairs[i], pairs[j]);
+                    pairs[i] = x; pairs[j] = y;
+                    let value = objective(&new_bound(sat, &pairs));
+                    if value < best { best = value; improved = true; break; }
+                    pairs[i] = old_x; pairs[j] = old_y;
+                }
+            }
+
+            if let Some(s) = single {
+                let (a, b) = pairs[i];
+                for (x, out) in [((s, b), a), ((a, s), b)] {
+                    pairs[i] = x;
+                    let value = objective(&new_bound(sat, &pairs));
+                    if value < best { best = value; improved = true; single = Some(out); break; }
+                    pairs[i] = (a, b);
+                }
+            }
+        }
+
+        if !improved { break; }
+    }
+
+    pairs
+}
+
+pub fn minimise_new_bound(sat: &SAT) -> SearchResult {
+    let greedy_match = Match::from_sat_greedy(sat);
+    let greedy = new_bound(sat, greedy_match.pairs());
+
+    if greedy == PredictedResult::Invalid(InvalidReason::TooLarge) {
+        return SearchResult { mat: greedy_match, bound: greedy, greedy };
+    }
+
+    let pairs = if sat.size() <= EXHAUSTIVE_LIMIT 
+        { exhaustive(sat) } 
+    else 
+        { local_search(sat, &greedy_match) };
+
+    let bound = new_bound(sat, &pairs);
+
+    // never report something worse than the starting point
+    if objective(&greedy) <= objective(&bound) {
+        return SearchResult { mat: greedy_match, bound: greedy, greedy };
+    }
+
+    SearchResult { mat: Match::from_pairs(pairs), bound, greedy }
+}
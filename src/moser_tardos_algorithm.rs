@@ -1,6 +1,8 @@
 use crate::sat::SAT;
-use crate::random_space::{random_random_space, random_space_of_nbits, InfiniteRandomSpace, LimitedRandomSpace, RandomSpace};
+use crate::event::{BadEvent, Instance, Value};
+use crate::random_space::{derive_seed, random_space_of_nbits, InfiniteRandomSpace, LimitedRandomSpace, RandomSpace, RecordingRandomSpace, TableRandomSpace};
 use crate::dep::DependencyGraph;
+use crate::r#match::Match;
 use std::collections::BTreeSet;
 
 #[derive(Debug)]
@@ -16,7 +18,8 @@ pub enum ExecuteResult<ResamplingType> {
 
 pub trait AlgorithmSimulator<R: RandomSpace> {
     type ResamplingType;
-    fn new(sat: SAT, random_space: R) -> Self;
+    type Instance: Instance;
+    fn new(instance: Self::Instance, random_space: R) -> Self;
     fn run_init(&mut self);
     fn run_next_step(&mut self) -> ExecuteResult<Self::ResamplingType>;
     fn run_until_terminal(&mut self) -> ExecuteResult<Self::ResamplingType> {
@@ -30,57 +33,46 @@ pub trait AlgorithmSimulator<R: RandomSpace> {
     }
     fn is_terminal(&self) -> bool;
     fn restart(&mut self, random_space: R);
+    fn random_space(&self) -> &R;
 }
 
-pub struct MTsAlgorithmSimulator<R: RandomSpace> {
-    sat:              SAT,
+pub struct MTsAlgorithmSimulator<R: RandomSpace, I: Instance = SAT> {
+    instance:         I,
     random_space:     R,
     violated_clause:  BTreeSet<usize>,
     dependency_graph: DependencyGraph,
-    varible:          Vec<bool>,
+    varible:          Vec<Value<I>>,
     step:             usize
 }
 
-fn resampling<R>(var: &mut Vec<bool>, random_space: &mut R, list: Vec<usize> ) -> Result<(),()> 
-    where R: RandomSpace
-{
-    for v in list {
-        let new_value = random_space.fetch_random_bit();
-        if new_value == None { return Err(()); }
-        var[v - 1] = new_value.unwrap();
-    }
-    
-    Ok(())
-}
-
-impl<R> MTsAlgorithmSimulator<R> 
-    where R: RandomSpace
+impl<R, I> MTsAlgorithmSimulator<R, I> 
+    where R: RandomSpace, I: Instance
 {
     #[cfg(test)]
-    pub fn get_varible(&self) -> &Vec<bool> {
+    pub fn get_varible(&self) -> &Vec<Value<I>> {
         &self.varible
     }
 }
 
-impl<R> AlgorithmSimulator<R> for MTsAlgorithmSimulator<R> 
-    where R: RandomSpace
+impl<R, I> AlgorithmSimulator<R> for MTsAlgorithmSimulator<R, I> 
+    where R: RandomSpace, I: Instance
 {
     type ResamplingType = usize;
-    fn new(sat: SAT, random_space: R) -> Self {
+    type Instance = I;
+    fn new(instance: I, random_space: R) -> Self {
         Self {
-            dependency_graph: DependencyGraph::form_sat(&sat),
-            varible: crate::new_vector( sat.variable_count(), false ),
+            dependency_graph: instance.dependency_graph(),
+            varible: crate::new_vector( instance.variable_count(), Value::<I>::default() ),
             violated_clause: BTreeSet::new(),
             step: 0,
-            sat, random_space
+            instance, random_space
         }
     }
 
     fn run_init(&mut self) {
-        for v in self.varible.iter_mut() {
-            *v = self.random_space.fetch_random_bit().unwrap();
-        }
-        for (id, &b) in self.sat.check_violated(&self.varible).iter().enumerate() {
+        let all: Vec<usize> = (0..self.varible.len()).collect();
+        self.instance.resample(&mut self.varible, &mut self.random_space, &all).unwrap();
+        for (id, &b) in self.instance.check_violated(&self.varible).iter().enumerate() {
             if b { self.violated_clause.insert(id); }
         }
     }
@@ -89,15 +81,15 @@ impl<R> AlgorithmSimulator<R> for MTsAlgorithmSimulator<R>
         match self.violated_clause.pop_first() {
             None => ExecuteResult::Terminal { step: self.step },
             Some(id) => {
-                match resampling(
+                match self.instance.resample(
                     &mut self.varible, 
                     &mut self.random_space, 
-                    self.sat.get_clause(id).collect_varible()) 
+                    &self.instance.events()[id].resampled_variables()) 
                 {
                     Err(_) => ExecuteResult::RandomSpaceExceed,
                     Ok(_) => {
                         for c in self.dependency_graph.get_gamma_plus(id) {
-                            if self.sat.get_clause(c).check_violated(&self.varible) {
+                            if self.instance.events()[c].check_violated(&self.varible) {
                                 self.violated_clause.insert(c);
                             }
                         }
@@ -119,10 +111,14 @@ impl<R> AlgorithmSimulator<R> for MTsAlgorithmSimulator<R>
         self.random_space = random_space;
         self.violated_clause.clear();
     }
+
+    fn random_space(&self) -> &R {
+        &self.random_space
+    }
 }
 
-impl<R> Iterator for MTsAlgorithmSimulator<R> 
-    where R : RandomSpace
+impl<R, I> Iterator for MTsAlgorithmSimulator<R, I> 
+    where R : RandomSpace, I: Instance
 {
     type Item = ExecuteResult<usize>;
     fn next(&mut self) -> Option<Self::Item> {
@@ -134,20 +130,21 @@ impl<R> Iterator for MTsAlgorithmSimulator<R>
     }
 }
 
-pub struct NewAlgorithmSimulator<R>
-    where R: RandomSpace 
+pub struct NewAlgorithmSimulator<R, I = SAT>
+    where R: RandomSpace, I: Instance
 {
-    inner: MTsAlgorithmSimulator<R>
+    inner: MTsAlgorithmSimulator<R, I>
 }
 
-impl<R> AlgorithmSimulator<R> for NewAlgorithmSimulator<R> 
-    where R: RandomSpace
+impl<R, I> AlgorithmSimulator<R> for NewAlgorithmSimulator<R, I> 
+    where R: RandomSpace, I: Instance
 {
     type ResamplingType = Vec<usize>;
+    type Instance = I;
 
-    fn new(sat: SAT, random_space: R) -> Self {
+    fn new(instance: I, random_space: R) -> Self {
         Self {
-            inner: MTsAlgorithmSimulator::new(sat, random_space)
+            inner: MTsAlgorithmSimulator::new(instance, random_space)
         }
     }
 
@@ -167,21 +164,21 @@ impl<R> AlgorithmSimulator<R> for NewAlgorithmSimulator<R>
                 inner.violated_clause.clear();
 
                 for &c in &violated {
-                    for v in inner.sat.get_clause(c).collect_varible() {
+                    for v in inner.instance.events()[c].variables() {
                         list.insert(v);
                     }
                 }
 
-                match resampling(
+                match inner.instance.resample(
                     &mut inner.varible, 
                     &mut inner.random_space, 
-                    list.into_iter().collect() 
+                    &list.into_iter().collect::<Vec<usize>>()
                 ) {
                     Err(_) => ExecuteResult::RandomSpaceExceed,
                     Ok(_) => {
                         for &c in &violated {
                             for cc in inner.dependency_graph.get_gamma_plus(c) {
-                                if inner.sat.get_clause(cc).check_violated(&inner.varible) {
+                                if inner.instance.events()[cc].check_violated(&inner.varible) {
                                     inner.violated_clause.insert(cc);
                                 }
                             }
@@ -202,9 +199,109 @@ impl<R> AlgorithmSimulator<R> for NewAlgorithmSimulator<R>
     fn restart(&mut self, random_space: R) {
         self.inner.restart(random_space)
     }
+
+    fn random_space(&self) -> &R {
+        self.inner.random_space()
+    }
+}
+
+impl<R, I> Iterator for NewAlgorithmSimulator<R, I> 
+    where R : RandomSpace, I: Instance
+{
+    type Item = ExecuteResult<Vec<usize>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.run_next_step() {
+            ExecuteResult::Running { resampled_clause } 
+                => Some(ExecuteResult::Running { resampled_clause }),
+            _ => None
+        }
+    }
+}
+
+// resamples a violated clause together with its matched partner, that is
+// the pair is treated as one merged event
+pub struct MatchedAlgorithmSimulator<R>
+    where R: RandomSpace 
+{
+    inner: MTsAlgorithmSimulator<R>,
+    partner: Vec<Option<usize>>
+}
+
+impl<R> AlgorithmSimulator<R> for MatchedAlgorithmSimulator<R> 
+    where R: RandomSpace
+{
+    type ResamplingType = Vec<usize>;
+    type Instance = SAT;
+
+    fn new(sat: SAT, random_space: R) -> Self {
+        let mut partner = crate::new_vector(sat.size(), None);
+        for &(u, v) in &Match::from_sat_indexed(&sat) {
+            partner[u] = Some(v);
+            partner[v] = Some(u);
+        }
+
+        Self {
+            inner: MTsAlgorithmSimulator::new(sat, random_space),
+            partner
+        }
+    }
+
+    fn run_init(&mut self) {
+        self.inner.run_init()
+    }
+    
+    fn run_next_step(&mut self) -> ExecuteResult<Self::ResamplingType> {
+        let inner = &mut self.inner;
+
+        match inner.violated_clause.pop_first() {
+            None => ExecuteResult::Terminal { step: inner.step },
+            Some(id) => {
+                let event: Vec<usize> = [Some(id), self.partner[id]].into_iter().flatten().collect();
+                let mut list = BTreeSet::new();
+
+                for &c in &event {
+                    inner.violated_clause.remove(&c);
+                    for v in inner.instance.events()[c].variables() {
+                        list.insert(v);
+                    }
+                }
+
+                match inner.instance.resample(
+                    &mut inner.varible, 
+                    &mut inner.random_space, 
+                    &list.into_iter().collect::<Vec<usize>>()
+                ) {
+                    Err(_) => ExecuteResult::RandomSpaceExceed,
+                    Ok(_) => {
+                        for &c in &event {
+                            for cc in inner.dependency_graph.get_gamma_plus(c) {
+                                if inner.instance.events()[cc].check_violated(&inner.varible) {
+                                    inner.violated_clause.insert(cc);
+                                }
+                            }
+                        }
+                        inner.step += 1;
+                        ExecuteResult::Running { resampled_clause: event }
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.inner.is_terminal()
+    }
+
+    fn restart(&mut self, random_space: R) {
+        self.inner.restart(random_space)
+    }
+
+    fn random_space(&self) -> &R {
+        self.inner.random_space()
+    }
 }
 
-impl<R> Iterator for NewAlgorithmSimulator<R> 
+impl<R> Iterator for MatchedAlgorithmSimulator<R> 
     where R : RandomSpace
 {
     type Item = ExecuteResult<Vec<usize>>;
@@ -226,12 +323,13 @@ pub enum BenchResult {
     }
 }
 
-pub fn bench_algorithm<A>(sat: &SAT, n: usize) -> BenchResult 
+pub fn bench_algorithm<A>(instance: &A::Instance, n: usize, seed: u64) -> BenchResult 
     where A: AlgorithmSimulator<InfiniteRandomSpace>
 {
+    // stream 0 for the warm-up run, stream i + 1 for the i-th repetition
     let mut sand_box = A::new(
-        sat.clone(), 
-        random_random_space()
+        instance.clone(), 
+        InfiniteRandomSpace::new(derive_seed(seed, 0) as usize)
     );
 
     let mut results = Vec::new();
@@ -247,8 +345,8 @@ pub fn bench_algorithm<A>(sat: &SAT, n: usize) -> BenchResult
         _ => {}
     }
 
-    for _ in 0..n {
-        sand_box.restart(random_random_space());
+    for i in 0..n {
+        sand_box.restart(InfiniteRandomSpace::new(derive_seed(seed, i as u64 + 1) as usize));
         sand_box.run_init();
         match sand_box.run_until_terminal() {
             ExecuteResult::Terminal { step } => { results.push(step as i32); }
@@ -262,6 +360,58 @@ pub fn bench_algorithm<A>(sat: &SAT, n: usize) -> BenchResult
     }
 }
 
+// like bench_algorithm, but the i-th repetition reads the resampling table
+// seeded by derive_seed(seed, i), so simulators given the same seed run on
+// the same tables
+pub fn table_algorithm<A>(instance: &A::Instance, n: usize, seed: u64) -> BenchResult 
+    where A: AlgorithmSimulator<TableRandomSpace<InfiniteRandomSpace>>
+{
+    const LIMITS: usize = 10000000;
+
+    let m = instance.variable_count();
+    let mut results = Vec::new();
+
+    for i in 0..n {
+        let mut sand_box = A::new(
+            instance.clone(), 
+            TableRandomSpace::from_seed(m, derive_seed(seed, i as u64))
+        );
+        sand_box.run_init();
+
+        let step = (0..=LIMITS).find_map( |_| match sand_box.run_next_step() {
+            ExecuteResult::Terminal { step } => Some(Some(step)),
+            ExecuteResult::RandomSpaceExceed => Some(None),
+            ExecuteResult::Running { .. } => None
+        } ).flatten();
+
+        match step {
+            None => { return BenchResult::Failed; }
+            Some(step) => { results.push(step as i32); }
+        }
+    }
+
+    BenchResult::Success { 
+        mean: crate::mean(results.as_slice() ).unwrap() as f64, 
+        sigma: crate::std_deviation(results.as_slice() ).unwrap() as f64
+    }
+}
+
+// the i-th repetition of bench_algorithm with the same seed, recorded
+pub fn record_algorithm<A>(instance: &A::Instance, seed: u64, i: usize) -> Option<(usize, LimitedRandomSpace)> 
+    where A: AlgorithmSimulator<RecordingRandomSpace<InfiniteRandomSpace>>
+{
+    let mut sand_box = A::new(
+        instance.clone(), 
+        RecordingRandomSpace::new(InfiniteRandomSpace::new(derive_seed(seed, i as u64 + 1) as usize))
+    );
+
+    sand_box.run_init();
+    match sand_box.run_until_terminal() {
+        ExecuteResult::Terminal { step } => { Some((step, sand_box.random_space().replay())) }
+        _ => { None }
+    }
+}
+
 impl std::fmt::Display for BenchResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -282,11 +432,12 @@ pub enum EnumResult {
 }
 
 pub fn enum_algorithm<A>(sat: &SAT, turn: usize) -> EnumResult 
-    where A: AlgorithmSimulator<LimitedRandomSpace>
+    where A: AlgorithmSimulator<LimitedRandomSpace, Instance = SAT>
 {
     let bits = sat.variable_count() * (turn + 1);
     
-    if bits > 25 { return EnumResult::Failed; }
+    // equally likely tapes only give fair coins
+    if bits > 25 || sat.is_biased() { return EnumResult::Failed; }
 
     let mut results = crate::new_vector(turn + 1, 0);
 
@@ -332,12 +483,12 @@ impl std::fmt::Display for EnumResult {
     }
 }
 
-pub fn run_algorithm<A>(sat: &SAT) -> bool 
+pub fn run_algorithm<A>(instance: &A::Instance, seed: u64) -> bool 
     where A: AlgorithmSimulator<InfiniteRandomSpace>
 {
     let mut sand_box = A::new(
-        sat.clone(), 
-        random_random_space()
+        instance.clone(), 
+        InfiniteRandomSpace::new(seed as usize)
     );
 
     sand_box.run_init();
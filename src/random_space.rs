@@ -3,17 +3,62 @@ use rand::prelude::*;
 pub trait RandomSpace : Iterator<Item = bool> {
     fn fetch_random_bit(&mut self) -> Option<bool>
       { self.next() }
+    // the next n <= 64 bits, the first one in the lowest bit
+    fn fetch_bits(&mut self, n: usize) -> Option<u64> {
+        debug_assert!( n <= 64 );
+        let mut ret = 0;
+        for i in 0..n {
+            if self.fetch_random_bit()? { ret |= 1 << i; }
+        }
+        Some(ret)
+    }
+    // the next bit of each of the (1-based) variables, the i-th in bit i;
+    // a shared stream ignores which variable asks
+    fn fetch_variable_bits(&mut self, vars: &[usize]) -> Option<u64>
+      { self.fetch_bits(vars.len()) }
+    // a value of variable v that is true with probability q: the bits of v
+    // spell a uniform number in [0, 1) until it differs from q, two bits
+    // are used on average
+    fn fetch_biased_bit(&mut self, v: usize, q: f64) -> Option<bool> {
+        if q == 0.5 { return Some(self.fetch_variable_bits(&[v])? & 1 == 1); }
+
+        let mut q = q;
+        loop {
+            if q <= 0.0 { return Some(false); }
+            if q >= 1.0 { return Some(true); }
+            q *= 2.0;
+            let digit = q >= 1.0;
+            if digit { q -= 1.0; }
+            let bit = self.fetch_variable_bits(&[v])? & 1 == 1;
+            if bit != digit { return Some(digit); }
+        }
+    }
+    // a value of variable v drawn from `distribution`: value i is taken with
+    // probability distribution[i] given that no smaller value was
+    fn fetch_value(&mut self, v: usize, distribution: &[f64]) -> Option<usize> {
+        let mut rest = 1.0;
+        for (i, &p) in distribution.iter().enumerate().take(distribution.len() - 1) {
+            if rest <= 0.0 { break; }
+            if self.fetch_biased_bit(v, (p / rest).min(1.0))? { return Some(i); }
+            rest -= p;
+        }
+        Some(distribution.len() - 1)
+    }
     fn size(&self) -> usize;
 }
 
 pub struct InfiniteRandomSpace {
-    rng: StdRng
+    rng: StdRng,
+    buffer: u64,    // unused bits of the last word, lowest first
+    left: usize     // number of them
 }
 
 impl InfiniteRandomSpace {
     pub fn new(seed: usize) -> Self {
         Self { 
-            rng: StdRng::seed_from_u64(seed as u64)
+            rng: StdRng::seed_from_u64(seed as u64),
+            buffer: 0,
+            left: 0
         }
     }
 }
@@ -21,28 +66,92 @@ impl InfiniteRandomSpace {
 impl Iterator for InfiniteRandomSpace {
     type Item = bool;
     fn next(&mut self) -> Option<Self::Item> {
-        Some( self.rng.next_u32() & 1 == 1 )
+        if self.left == 0 {
+            self.buffer = self.rng.next_u64();
+            self.left = 64;
+        }
+        let bit = self.buffer & 1 == 1;
+        self.buffer >>= 1;
+        self.left -= 1;
+        Some(bit)
     }
 }
 
 impl RandomSpace for InfiniteRandomSpace {
+    fn fetch_bits(&mut self, n: usize) -> Option<u64> {
+        debug_assert!( n <= 64 );
+        if n == 0 { return Some(0); }
+
+        let low = self.buffer;
+        let have = self.left;
+        if n <= have {
+            self.buffer = if n == 64 { 0 } else { low >> n };
+            self.left -= n;
+            return Some(low & (u64::MAX >> (64 - n)));
+        }
+
+        // all the buffered bits, then the rest from a fresh word
+        let word = self.rng.next_u64();
+        let need = n - have;
+        let high = word & (u64::MAX >> (64 - need));
+        self.buffer = if need == 64 { 0 } else { word >> need };
+        self.left = 64 - need;
+        Some(low | high << have)
+    }
+
     fn size(&self) -> usize {
         2147483647
     }
 }
 
+// a finite tape of bits, handed out in order
+#[derive(Clone)]
 pub struct LimitedRandomSpace {
     pointer: usize,
-    seed: usize,
+    tape: Vec<u64>,
     limit: usize,
 }
 
 impl LimitedRandomSpace {
+    // the lowest `limit` bits of `seed`, as enumerated by random_space_of_nbits
     pub fn new(seed: usize, limit: usize) -> Self {
+        debug_assert!( limit <= usize::BITS as usize );
         Self {
-            pointer: 0, seed, limit
+            pointer: 0, tape: vec![seed as u64], limit
         }
     }
+
+    pub fn from_bits<I: IntoIterator<Item = bool>>(bits: I) -> Self {
+        let mut ret = Self { pointer: 0, tape: Vec::new(), limit: 0 };
+        for b in bits { ret.push(b); }
+        ret
+    }
+
+    // '0' and '1', whitespace is ignored
+    pub fn from_bit_string(str: &str) -> Self {
+        Self::from_bits( str.chars().filter(|c| !c.is_whitespace()).map( |c| match c {
+            '0' => false,
+            '1' => true,
+            _ => panic!("a tape should only contain 0 and 1, found {c:?}")
+        } ) )
+    }
+
+    pub fn from_file(path: &str) -> Self {
+        let content = std::fs::read_to_string(path)
+            .expect("Should have been able to read the file");
+
+        Self::from_bit_string(&content)
+    }
+
+    pub fn push(&mut self, bit: bool) {
+        if self.limit == self.tape.len() * 64 { self.tape.push(0); }
+        if bit { self.tape[self.limit / 64] |= 1 << (self.limit % 64); }
+        self.limit += 1;
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        (self.tape[i / 64] >> (i % 64)) & 1 == 1
+    }
 }
 
 impl Iterator for LimitedRandomSpace {
@@ -52,7 +161,7 @@ impl Iterator for LimitedRandomSpace {
             { None }
         else {
             self.pointer += 1;
-            Some((self.seed >> (self.pointer - 1)) & 1 == 1)
+            Some(self.bit(self.pointer - 1))
         }
     }
 }
@@ -65,11 +174,96 @@ impl RandomSpace for LimitedRandomSpace {
 
 impl std::fmt::Debug for LimitedRandomSpace {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let str : String = (0..self.limit).map( |i| if (self.seed >> i) & 1 == 1 {"1"} else {"0"}).collect();
+        let str : String = (0..self.limit).map( |i| if self.bit(i) {"1"} else {"0"}).collect();
         write!(f, "{}", str)
     }
 }
 
+// hands out the bits of `inner` and keeps a log of them
+pub struct RecordingRandomSpace<R: RandomSpace> {
+    inner: R,
+    log: LimitedRandomSpace
+}
+
+impl<R: RandomSpace> RecordingRandomSpace<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner, log: LimitedRandomSpace::from_bits([]) }
+    }
+
+    // a tape replaying exactly the bits handed out so far
+    pub fn replay(&self) -> LimitedRandomSpace {
+        let mut tape = self.log.clone();
+        tape.pointer = 0;
+        tape
+    }
+}
+
+impl<R: RandomSpace> Iterator for RecordingRandomSpace<R> {
+    type Item = bool;
+    fn next(&mut self) -> Option<Self::Item> {
+        let bit = self.inner.fetch_random_bit()?;
+        self.log.push(bit);
+        Some(bit)
+    }
+}
+
+impl<R: RandomSpace> RandomSpace for RecordingRandomSpace<R> {
+    fn fetch_bits(&mut self, n: usize) -> Option<u64> {
+        let bits = self.inner.fetch_bits(n)?;
+        for i in 0..n { self.log.push((bits >> i) & 1 == 1); }
+        Some(bits)
+    }
+
+    fn size(&self) -> usize {
+        self.inner.size()
+    }
+}
+
+// the resampling table of the Moser-Tardos analysis: every variable reads
+// its own column in order, whatever order the clauses are resampled in
+pub struct TableRandomSpace<R: RandomSpace> {
+    column: Vec<R>
+}
+
+impl<R: RandomSpace> TableRandomSpace<R> {
+    pub fn new(column: Vec<R>) -> Self {
+        Self { column }
+    }
+
+    #[cfg(test)]
+    pub fn column(&self, v: usize) -> &R {
+        &self.column[v - 1]
+    }
+}
+
+impl TableRandomSpace<InfiniteRandomSpace> {
+    pub fn from_seed(m: usize, seed: u64) -> Self {
+        Self::new( (0..m).map( |v| InfiniteRandomSpace::new(derive_seed(seed, v as u64) as usize) ).collect() )
+    }
+}
+
+impl<R: RandomSpace> Iterator for TableRandomSpace<R> {
+    type Item = bool;
+    // bits only exist per variable
+    fn next(&mut self) -> Option<Self::Item> {
+        None
+    }
+}
+
+impl<R: RandomSpace> RandomSpace for TableRandomSpace<R> {
+    fn fetch_variable_bits(&mut self, vars: &[usize]) -> Option<u64> {
+        let mut ret = 0;
+        for (i, &v) in vars.iter().enumerate() {
+            if self.column[v - 1].fetch_random_bit()? { ret |= 1 << i; }
+        }
+        Some(ret)
+    }
+
+    fn size(&self) -> usize {
+        self.column.iter().map( |c| c.size() ).fold(0, usize::saturating_add)
+    }
+}
+
 pub struct RandomSpaceIterator {
     bits: usize, 
     now: usize,
@@ -94,7 +288,10 @@ pub fn random_space_of_nbits(n: usize) -> RandomSpaceIterator {
     }
 }
 
-pub fn random_random_space() -> InfiniteRandomSpace {
-    let mut rng = rand::thread_rng();
-    InfiniteRandomSpace::new(rng.next_u64() as usize)
-}
\ No newline at end of file
+// splitmix64 finaliser, turns (seed, stream) into an independent seed
+pub fn derive_seed(seed: u64, stream: u64) -> u64 {
+    let mut z = seed.wrapping_add(stream.wrapping_add(1).wrapping_mul(0x9e3779b97f4a7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
@@ -1,6 +1,14 @@
 use core::slice;
 use std::collections::BTreeSet;
 
+use crate::event::{BadEvent, Instance};
+use crate::density::{self, DensityEstimate};
+use crate::random_space::RandomSpace;
+
+// literals the exact count may branch on before the density is left to the
+// estimate
+const COUNT_BUDGET: usize = 10000000;
+
 #[derive(Clone)]
 pub struct Clause {
     literal : Vec<isize>
@@ -12,7 +20,8 @@ pub struct SAT {
     n: usize,              // number of clause
     m: usize,              // number of variable
     k: usize,              // number of literal in one clause
-    clause : Vec<Clause>
+    clause : Vec<Clause>,
+    bias: Vec<f64>         // P(x_i = 1)
 }
 
 impl Clause {
@@ -28,7 +37,7 @@ impl Clause {
         Self { literal: Vec::from(array) }
     }
 
-    pub fn check_violated(&self, var: &Vec<bool>) -> bool {
+    pub fn check_violated(&self, var: &[bool]) -> bool {
         self.literal.iter().map(
             |liter| {
                 let pos = (liter.abs() - 1) as usize;
@@ -46,6 +55,36 @@ impl Clause {
     }
 }
 
+impl BadEvent for Clause {
+    type Value = bool;
+    // P(x = 1)
+    type Distribution = f64;
+
+    fn variables(&self) -> Vec<usize> {
+        let set: BTreeSet<usize> = self.literal.iter().map(|l| l.unsigned_abs() - 1).collect();
+        set.into_iter().collect()
+    }
+
+    // in literal order, as the bits of a seeded run were always drawn
+    fn resampled_variables(&self) -> Vec<usize> {
+        self.literal.iter().map(|l| l.unsigned_abs() - 1).collect()
+    }
+
+    fn check_violated(&self, value: &[bool]) -> bool {
+        Clause::check_violated(self, value)
+    }
+
+    fn probability(&self, distribution: &[f64]) -> f64 {
+        let lits: BTreeSet<isize> = self.literal.iter().copied().collect();
+        if lits.iter().any(|l| lits.contains(&-l)) { return 0.0; }
+
+        lits.into_iter().map( |l| {
+            let q = distribution[l.unsigned_abs() - 1];
+            if l > 0 { q } else { 1.0 - q }
+        } ).product()
+    }
+}
+
 impl<'a> IntoIterator for &'a Clause {
     type Item = &'a isize;
     type IntoIter = slice::Iter<'a, isize>;
@@ -65,7 +104,8 @@ impl SAT {
     pub fn new(n: usize, m: usize, k: usize) -> Self {
         SAT {
             n, m, k, 
-            clause: crate::new_vector(n, Clause::new(k))
+            clause: crate::new_vector(n, Clause::new(k)),
+            bias: crate::new_vector(m, 0.5)
         }
     }
 
@@ -87,13 +127,6 @@ impl SAT {
     }
 
     pub fn from_vec(vec: Vec<Vec<isize>>) -> Self {
-        let mut k = vec[0].len();
-        for v in &vec 
-          { if v.len() != k {k = 0;} }
-        
-        if vec[0].ends_with(&[0]) && k != 0
-          { k -= 1 }
-
         let mut set = std::collections::BTreeSet::new();
         
         for v in &vec {
@@ -101,15 +134,46 @@ impl SAT {
                 if *l != 0 { set.insert(l.abs()); }
             }
         }
+
+        let clause: Vec<Clause> = vec.into_iter().map( Clause::from_vec ).collect();
+
+        // 0 for mixed widths
+        let mut k = clause[0].size();
+        for c in &clause 
+          { if c.size() != k {k = 0;} }
         
+        let m = set.len().max( *set.iter().max().unwrap() as usize );
+
         Self {
-            n: vec.len(),
-            m: set.len().max( *set.iter().max().unwrap() as usize ),
+            n: clause.len(),
+            m,
             k,
-            clause: vec.into_iter().map( Clause::from_vec ).collect()
+            clause,
+            bias: crate::new_vector(m, 0.5)
         }
     }
 
+    pub fn with_bias(mut self, bias: Vec<f64>) -> Self {
+        assert_eq!( bias.len(), self.m );
+        assert!( bias.iter().all(|q| (0.0..=1.0).contains(q)) );
+        self.bias = bias;
+        self
+    }
+
+    pub fn bias(&self) -> &[f64] {
+        &self.bias
+    }
+
+    pub fn is_biased(&self) -> bool {
+        self.bias.iter().any(|&q| q != 0.5)
+    }
+
+    // probability that the literal is true
+    pub fn pr_literal(&self, l: isize) -> f64 {
+        let q = self.bias[l.unsigned_abs() - 1];
+        if l > 0 { q } else { 1.0 - q }
+    }
+
     pub fn from_array<const K: usize, const N : usize>(array: [[isize; K]; N]) -> Self {
         Self::from_vec( array.into_iter().map( Vec::from ).collect() )
     }
@@ -130,7 +194,28 @@ impl SAT {
             }
         }
         
-        return 1.0 / (1 << set.len()) as f64
+        return set.into_iter().map(|l| self.pr_literal(l)).product()
+    }
+
+    pub fn is_disjoint( &self, u: usize, v: usize ) -> bool {
+        let var = self.clause[u].collect_varible();
+        self.clause[v].collect_varible().iter().all(|x| !var.contains(x))
+    }
+
+    pub fn pr_land_pair( &self, u: usize, v: usize ) -> f64 {
+        self.pr_land_exact(&[u, v])
+    }
+
+    pub fn pr_land_exact( &self, clauses_id: &[usize] ) -> f64 {
+        // pr_land without the size cut-off
+        let mut lits: Vec<isize> = clauses_id.iter()
+            .flat_map(|&c| self.clause[c].literal.iter()).copied().collect();
+        lits.sort();
+        lits.dedup();
+
+        if lits.iter().any(|l| lits.binary_search(&-l).is_ok()) { return 0.0; }
+
+        lits.into_iter().map(|l| self.pr_literal(l)).product()
     }
 
     pub fn get_clause(&self, id: usize) -> &Clause {
@@ -144,6 +229,56 @@ impl SAT {
     pub fn clause_size(&self) -> usize { self.k }
 }
 
+impl Instance for SAT {
+    type Event = Clause;
+
+    fn events(&self) -> &[Clause] {
+        &self.clause
+    }
+
+    fn distribution(&self) -> &[f64] {
+        &self.bias
+    }
+
+    fn sample<R: RandomSpace>(&self, v: usize, random_space: &mut R) -> Option<bool> {
+        random_space.fetch_biased_bit(v + 1, self.bias[v])
+    }
+
+    fn resample<R: RandomSpace>(&self, value: &mut [bool], random_space: &mut R, list: &[usize]) -> Result<(),()> {
+        if list.iter().any(|&v| self.bias[v] != 0.5) {
+            for &v in list {
+                value[v] = self.sample(v, random_space).ok_or(())?;
+            }
+            return Ok(());
+        }
+
+        // fair coins come 64 at a time
+        for chunk in list.chunks(64) {
+            let vars: Vec<usize> = chunk.iter().map(|v| v + 1).collect();
+            let bits = random_space.fetch_variable_bits(&vars).ok_or(())?;
+            for (i, &v) in chunk.iter().enumerate() {
+                value[v] = (bits >> i) & 1 == 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn log2_density(&self) -> Option<f64> {
+        if self.is_biased() {
+            crate::counting::solution_density(self, COUNT_BUDGET).map(|d| d.log2())
+        } else {
+            crate::counting::count_solutions(self, COUNT_BUDGET).map( |c| 
+                if c.is_zero() { f64::NEG_INFINITY } else { c.log2() - self.m as f64 }
+            )
+        }
+    }
+
+    fn estimate_density<R: RandomSpace>(&self, samples: usize, random_space: &mut R) -> DensityEstimate {
+        density::sequential_importance_sampling(self, samples, random_space)
+    }
+}
+
 impl std::fmt::Debug for SAT {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self.clause )
@@ -21,7 +21,7 @@ fn check_moser_tardos_simulator() {
 
     let mut sand_box = 
         moser_tardos_algorithm::MTsAlgorithmSimulator::new
-            (sat.clone(), random_space::random_random_space());
+            (sat.clone(), random_space::InfiniteRandomSpace::new(1));
 
     
     assert!( match sand_box.run_until_terminal() {
@@ -69,7 +69,7 @@ fn lll_test() {
     let sat = SAT::from_array(sat);
     let dep = DependencyGraph::form_sat(&sat);
 
-    println!( "{}", lll::satisfiability_checker(&sat) );
+    println!( "{}", lll::satisfiability_checker(&sat, 0) );
     println!( "{}", lll::shearers_bound_checker(&dep) );
 }
 
@@ -90,4 +90,804 @@ fn non_cnf_test() {
     let dep = DependencyGraph::form_sat(&sat);
 
     println!("{}", dep.max_p);
-}
\ No newline at end of file
+}
+#[test]
+fn tail_bound_test() {
+    use crate::lll;
+    use crate::lll::PredictedResult;
+    use crate::sat::SAT;
+    use crate::dep::DependencyGraph;
+    use crate::moser_tardos_algorithm;
+    use crate::random_space;
+
+    let sat = [[1, 2, 3], [-3, 4, 5], [5, 6, 7], [-7, -1, 8]];
+    let sat = SAT::from_array(sat);
+    let dep = DependencyGraph::form_sat(&sat);
+
+    let exact = match moser_tardos_algorithm::enum_algorithm::<
+        moser_tardos_algorithm::MTsAlgorithmSimulator<
+            random_space::LimitedRandomSpace
+        >
+    >(&sat, 1) {
+        moser_tardos_algorithm::EnumResult::Success { p } => p,
+        _ => { panic!() }
+    };
+
+    for t in 1..=2 {
+        for bound in [
+            lll::symmertric_lll_tail_bound(&dep, t),
+            lll::asymmetric_lll_tail_bound(&dep, t),
+            lll::shearers_tail_bound(&dep, t),
+        ] {
+            match bound {
+                PredictedResult::UpperBound(up) => { assert!( exact[t - 1] <= up + 1e-9 ); }
+                _ => { panic!(); }
+            }
+        }
+    }
+}
+
+#[test]
+fn counting_test() {
+    use crate::sat::SAT;
+    use crate::counting;
+    use rand::prelude::*;
+
+    // no two consecutive ones among 150 bits: Fibonacci(152) solutions
+    let sat = SAT::from_vec( (1..150).map(|i| vec![i, i + 1]).collect() );
+
+    let (mut a, mut b) = (1u128, 1u128);
+    for _ in 0..150 { (a, b) = (b, a + b); }
+
+    let count = counting::count_solutions(&sat, 1000000).unwrap();
+    assert_eq!( format!("{}", count), format!("{}", b) );
+
+    // mixed widths, repeated literals and units against enumeration
+    let mut rng = StdRng::seed_from_u64(27);
+    for _ in 0..200 {
+        let m = rng.gen_range(1..=12isize);
+        let sat = SAT::from_vec( (0..rng.gen_range(1..=30)).map( |_| {
+            (0..rng.gen_range(1..=4)).map( |_| {
+                let v = rng.gen_range(1..=m);
+                if rng.gen() { v } else { -v }
+            } ).collect()
+        } ).collect() );
+
+        let m = sat.variable_count();
+        let expect = (0..1usize << m).filter( |mask| {
+            let var: Vec<bool> = (0..m).map(|i| mask >> i & 1 == 1).collect();
+            sat.check_violated(&var).iter().all(|&b| !b)
+        } ).count();
+        assert_eq!( format!("{}", counting::count_solutions(&sat, 1000000).unwrap()), format!("{}", expect) );
+    }
+}
+
+#[test]
+fn exact_satisfiability_test() {
+    use crate::sat::SAT;
+    use crate::lll::{self, PredictedResult};
+    use crate::counting;
+    use rand::prelude::*;
+
+    // 60 random clauses of width 3 over 120 variables
+    let mut rng = StdRng::seed_from_u64(2027);
+    let sat = SAT::from_vec( (0..60).map( |_| {
+        (0..3).map( |_| {
+            let v = rng.gen_range(1..=120isize);
+            if rng.gen() { v } else { -v }
+        } ).collect()
+    } ).collect() );
+    assert!( sat.variable_count() > 100 );
+
+    let count = counting::count_solutions(&sat, 10000000).unwrap();
+    let expect = (sat.variable_count() as f64 - count.log2()).exp2();
+
+    match lll::satisfiability_checker(&sat, 0) {
+        PredictedResult::UpperBound(x) => { assert!( (x - expect).abs() < 1e-9 * expect ); }
+        r => { panic!("{r}"); }
+    }
+
+    match lll::estimated_satisfiability_checker(&sat, 20000, 1) {
+        PredictedResult::Estimate { lower, upper, .. } => { assert!( lower <= expect && expect <= upper ); }
+        _ => { panic!(); }
+    }
+}
+
+#[test]
+fn cdcl_test() {
+    use crate::sat::SAT;
+    use crate::cdcl;
+    use crate::counting;
+    use rand::prelude::*;
+
+    let budget = cdcl::Budget { conflicts: 100000, time: std::time::Duration::from_secs(10) };
+    let mut rng = StdRng::seed_from_u64(2023);
+
+    for _ in 0..50 {
+        let sat = SAT::from_vec( (0..85).map( |_| {
+            (0..3).map( |_| {
+                let v = rng.gen_range(1..=20isize);
+                if rng.gen() { v } else { -v }
+            } ).collect()
+        } ).collect() );
+
+        let count = counting::count_solutions(&sat, 1000000).unwrap();
+
+        match cdcl::solve(&sat, &budget) {
+            cdcl::SolveResult::Satisfiable(var) => {
+                assert!( !count.is_zero() );
+                assert!( sat.check_violated(&var).iter().all(|&b| !b) );
+            }
+            cdcl::SolveResult::Unsatisfiable => { assert!( count.is_zero() ); }
+            cdcl::SolveResult::Unknown => { panic!(); }
+        }
+    }
+}
+
+
+#[test]
+fn density_estimate_test() {
+    use crate::sat::SAT;
+    use crate::density;
+    use crate::random_space::InfiniteRandomSpace;
+
+    // no two consecutive ones among 60 bits: density Fibonacci(62) / 2^60 ~ 3.5e-6
+    let sat = SAT::from_vec( (1..60).map(|i| vec![i, i + 1]).collect() );
+
+    let (mut a, mut b) = (1u64, 1u64);
+    for _ in 0..60 { (a, b) = (b, a + b); }
+    let exact = b as f64 / (60f64).exp2();
+
+    let est = density::sequential_importance_sampling(&sat, 2000, &mut InfiniteRandomSpace::new(7));
+    assert!( est.lower <= exact && exact <= est.upper );
+    assert!( est.upper - est.lower < exact );
+}
+
+#[test]
+fn predicted_result_test() {
+    use crate::lll;
+    use crate::lll::{PredictedResult, InvalidReason};
+    use crate::sat::SAT;
+    use crate::dep::DependencyGraph;
+
+    assert_eq!( PredictedResult::UpperBound(2.0), PredictedResult::UpperBound(2.0 + 1e-12) );
+    assert_ne!( PredictedResult::UpperBound(2.0), PredictedResult::UpperBound(2.001) );
+
+    let sat = SAT::from_vec( vec![vec![1], vec![-1]] );
+    assert_eq!( lll::satisfiability_checker(&sat, 0), PredictedResult::Invalid(InvalidReason::Unsatisfiable) );
+
+    let sat = SAT::from_vec( (1..=21).map(|i| vec![i]).collect() );
+    let dep = DependencyGraph::form_sat(&sat);
+    assert_eq!( lll::shearers_bound_checker(&dep), PredictedResult::Invalid(InvalidReason::TooLarge) );
+}
+
+#[test]
+fn criterion_registry_test() {
+    use crate::criterion;
+    use crate::sat::SAT;
+    use crate::dep::DependencyGraph;
+
+    let criteria = criterion::registry();
+    let keys: std::collections::BTreeSet<String> = criteria.iter().map(|c| c.key()).collect();
+    assert_eq!( keys.len(), criteria.len() );
+
+    let sat = SAT::from_array([[1, 2, 3], [-3, 4, 5], [5, 6, 7]]);
+    let dep = DependencyGraph::form_sat(&sat);
+    for c in &criteria {
+        assert!( !c.evaluate(&criterion::Evaluation::new(&sat, &dep, 0)).is_invalid(), "{}", c.name() );
+    }
+}
+
+#[test]
+fn blossom_test() {
+    use crate::blossom::max_weight_matching;
+    use rand::prelude::*;
+
+    fn brute(n: usize, w: &Vec<Vec<i64>>, used: u32) -> i64 {
+        let u = match (0..n).find(|&u| used >> u & 1 == 0) { None => { return 0; } Some(u) => u };
+        let mut best = brute(n, w, used | 1 << u);
+        for v in u + 1..n {
+            if used >> v & 1 == 0 && w[u][v] > 0 {
+                best = best.max(w[u][v] + brute(n, w, used | 1 << u | 1 << v));
+            }
+        }
+        best
+    }
+
+    let mut rng = StdRng::seed_from_u64(32);
+    for _ in 0..300 {
+        let n = rng.gen_range(1..=10);
+        let mut w = vec![vec![0i64; n]; n];
+        let mut edges = Vec::new();
+        for (u, row) in w.iter_mut().enumerate() { for (v, weight) in row.iter_mut().enumerate().skip(u + 1) {
+            if rng.gen_bool(0.5) {
+                *weight = rng.gen_range(1..=20);
+                edges.push((u, v, *weight));
+            }
+        } }
+
+        let mate = max_weight_matching(n, &edges);
+        let mut total = 0;
+        for (u, &m) in mate.iter().enumerate() { if let Some(v) = m {
+            assert_eq!( mate[v], Some(u) );
+            assert!( w[u.min(v)][u.max(v)] > 0 );
+            if u < v { total += w[u][v]; }
+        } }
+
+        assert_eq!( total, brute(n, &w, 0) );
+    }
+}
+
+#[test]
+fn max_weight_match_test() {
+    use crate::sat::SAT;
+    use crate::r#match::Match;
+    use rand::prelude::*;
+
+    let mut rng = StdRng::seed_from_u64(11);
+    for _ in 0..20 {
+        let sat = SAT::from_vec( (0..12).map( |_| {
+            (0..3).map( |_| {
+                let v = rng.gen_range(1..=10isize);
+                if rng.gen() { v } else { -v }
+            } ).collect()
+        } ).collect() );
+
+        let greedy = Match::from_sat_greedy(&sat).total_weight(&sat);
+        let best = Match::from_sat_max_weight(&sat).total_weight(&sat);
+        assert!( best >= greedy * (1.0 - 1e-9) );
+    }
+}
+
+#[test]
+fn match_search_test() {
+    use crate::sat::SAT;
+    use crate::match_search;
+    use crate::lll::PredictedResult;
+
+    use crate::dep::DependencyGraph;
+    use crate::lll;
+    use crate::r#match::Match;
+    use rand::prelude::*;
+
+    fn new_bound(sat: &SAT, pairs: &[(usize, usize)]) -> f64 {
+        let dep = DependencyGraph::from_sat_with_match(sat, &Match::from_pairs(pairs.to_vec()));
+        match lll::shearers_bound_checker(&dep) {
+            PredictedResult::UpperBound(up) => { up }
+            _ => { f64::INFINITY }
+        }
+    }
+
+    fn is_matching(n: usize, pairs: &[(usize, usize)]) -> bool {
+        let mut used = vec![false; n];
+        pairs.iter().all( |&(u, v)| {
+            let ok = u != v && u < n && v < n && !used[u] && !used[v];
+            used[u] = true; used[v] = true;
+            ok
+        } )
+    }
+
+    // the optimum over every matching, perfect or not, by subsets of pairs
+    let sat = SAT::from_array([[1, 2, 3], [1, 2, 4], [3, 4, 5], [-5, 6, 7], [6, 7, 8], [1, 8, 9], [2, 9, 10]]);
+    let n = sat.size();
+    let all: Vec<(usize, usize)> = (0..n).flat_map(|u| (u + 1..n).map(move |v| (u, v))).collect();
+    let optimum = (0..1usize << all.len())
+        .map( |mask| all.iter().enumerate().filter(|(i, _)| (mask >> i) & 1 == 1).map(|(_, &e)| e).collect::<Vec<_>>() )
+        .filter( |pairs| is_matching(n, pairs) )
+        .map( |pairs| new_bound(&sat, &pairs) )
+        .fold(f64::INFINITY, f64::min);
+
+    let result = match_search::minimise_new_bound(&sat);
+    assert_eq!( result.bound, PredictedResult::UpperBound(optimum) );
+    assert!( result.improvement() >= 0.0 );
+
+    // beyond the exhaustive limit the local search gives a matching no worse
+    // than greedy
+    let mut rng = StdRng::seed_from_u64(33);
+    let sat = SAT::from_vec( (0..14).map( |_| {
+        rand::seq::index::sample(&mut rng, 30, 5).into_iter().map( |v| {
+            let v = v as isize + 1;
+            if rng.gen() { v } else { -v }
+        } ).collect()
+    } ).collect() );
+    let result = match_search::minimise_new_bound(&sat);
+    assert!( matches!( result.greedy, PredictedResult::UpperBound(_) ) );
+    assert!( is_matching(sat.size(), result.mat.pairs()) );
+    assert_eq!( result.bound, PredictedResult::UpperBound(new_bound(&sat, result.mat.pairs())) );
+    assert!( result.improvement() >= 0.0 );
+}
+
+#[test]
+fn indexed_match_test() {
+    use crate::sat::SAT;
+    use crate::r#match::{self, Match};
+    use rand::prelude::*;
+
+    let mut rng = StdRng::seed_from_u64(34);
+    let n = 50000;
+    let sat = SAT::from_vec( (0..n).map( |_| {
+        (0..3).map( |_| {
+            let v = rng.gen_range(1..=20000isize);
+            if rng.gen() { v } else { -v }
+        } ).collect()
+    } ).collect() );
+
+    let mat = Match::from_sat_indexed(&sat);
+    let mut used = vec![false; n];
+    for &(u, v) in &mat {
+        assert!( !used[u] && !used[v] );
+        used[u] = true; used[v] = true;
+        assert!( r#match::pairwise_weight(&sat, u, v) > 0.0 );
+    }
+    assert!( mat.pairs().len() > n / 4 );
+}
+
+#[test]
+fn grouping_test() {
+    use crate::sat::SAT;
+    use crate::dep::{self, DependencyGraph};
+    use crate::grouping::Grouping;
+    use crate::r#match::Match;
+
+    let sat = SAT::from_array([[1, 2, 3], [1, 2, 4], [1, 5, 6], [-1, 7, 8], [7, 9, 10], [11, 12, 13]]);
+
+    // groups of two give back the pairwise correction
+    let mat = Match::from_sat_indexed(&sat);
+    let paired = DependencyGraph::from_sat_with_match(&sat, &mat);
+    let grouped = DependencyGraph::from_sat_with_grouping(&sat, &Grouping::from_match(&mat), dep::MATCH_CONSTANT);
+    for i in 0..sat.size() {
+        assert!( (paired.p[i] - grouped.p[i]).abs() < 1e-12 );
+    }
+
+    for k in 2..=4 {
+        let grouping = Grouping::from_sat_greedy(&sat, k);
+        let mut seen = std::collections::BTreeSet::new();
+        for g in &grouping {
+            assert!( g.len() >= 2 && g.len() <= k );
+            for &u in g { assert!( seen.insert(u) ); }
+        }
+        // the clause sharing no variable is never grouped
+        assert!( !seen.contains(&5) );
+    }
+
+    // {0, 1, 2} all share x1: pairs are 1/16, 1/32, 1/32 and the triple 1/64
+    let c = Grouping::correction(&sat, &[0, 1, 2]);
+    let pair = |pr: f64| pr * pr;
+    assert!( (c[0] - (pair(1.0 / 16.0) + pair(1.0 / 32.0) - pair(1.0 / 64.0))).abs() < 1e-15 );
+    assert!( (c[2] - (pair(1.0 / 32.0) * 2.0 - pair(1.0 / 64.0))).abs() < 1e-15 );
+}
+
+#[test]
+fn correction_constant_test() {
+    use crate::sat::SAT;
+    use crate::dep::DependencyGraph;
+    use crate::r#match::Match;
+
+    let sat = SAT::from_array([[1, 2, 3], [1, 2, 4], [-1, 5, 6], [5, 7, 8]]);
+    let mat = Match::from_sat_indexed(&sat);
+
+    let new = DependencyGraph::from_sat_with_match(&sat, &mat);
+    let con = DependencyGraph::from_sat_with_match_conjecture(&sat, &mat);
+    let c13 = DependencyGraph::from_sat_with_constant(&sat, &mat, 13.0);
+    let c2 = DependencyGraph::from_sat_with_constant(&sat, &mat, 2.0);
+    let zero = DependencyGraph::from_sat_with_correction(&sat, &mat, |_, _, _| 0.0);
+    let plain = DependencyGraph::form_sat(&sat);
+
+    assert_eq!( new.p, c13.p );
+    assert_eq!( con.p, c2.p );
+    assert_eq!( zero.p, plain.p );
+    assert!( new.p.iter().zip(con.p.iter()).all(|(a, b)| a >= b) );
+    assert!( new.p.iter().zip(plain.p.iter()).any(|(a, b)| a < b) );
+}
+
+#[test]
+fn quotient_graph_test() {
+    use crate::sat::SAT;
+    use crate::dep::DependencyGraph;
+    use crate::r#match::Match;
+    use crate::lll::{self, PredictedResult};
+
+    // a path 0 - 1 - 2 - 3 with 0, 1 and 2, 3 matched
+    let sat = SAT::from_array([[1, 2, 3], [1, 4, 5], [4, 6, 7], [6, 8, 9]]);
+    let mat = Match::from_pairs(vec![(0, 1), (2, 3)]);
+    let dep = DependencyGraph::quotient(&sat, &mat);
+
+    assert_eq!( dep.n, 2 );
+    assert_eq!( dep.m, 1 );
+    assert_eq!( dep.get_gamma(0), &vec![1] );
+    // P(A or B) = 1/8 + 1/8 - 1/32
+    assert!( (dep.get_p(0) - 7.0 / 32.0).abs() < 1e-15 );
+    assert!( (dep.get_p(1) - 7.0 / 32.0).abs() < 1e-15 );
+
+    // an empty matching leaves the graph as it is
+    let plain = DependencyGraph::form_sat(&sat);
+    let same = DependencyGraph::quotient(&sat, &Match::from_pairs(vec![]));
+    assert_eq!( same.p, plain.p );
+    assert_eq!( lll::shearers_bound_checker(&same), lll::shearers_bound_checker(&plain) );
+
+    match lll::shearers_bound_checker(&dep) {
+        PredictedResult::UpperBound(x) => { assert!( x > 0.0 ); }
+        _ => { panic!(); }
+    }
+}
+
+#[test]
+fn mixed_width_test() {
+    use crate::sat::SAT;
+    use crate::dep::DependencyGraph;
+    use crate::r#match::Match;
+
+    let sat = SAT::from_vec(vec![ vec![1, 2, 0], vec![1, 3, 4, 0], vec![5, 0], vec![6, 7, 8, 9, 0] ]);
+    assert_eq!( sat.clause_size(), 0 );
+    assert_eq!( SAT::from_vec(vec![ vec![1, 2, 0], vec![3, 4, 0] ]).clause_size(), 2 );
+
+    let dep = DependencyGraph::form_sat(&sat);
+    assert_eq!( dep.p, vec![0.25, 0.125, 0.5, 0.0625] );
+    assert_eq!( dep.max_p, 0.5 );
+    // a star 1 - 0 - 2 has degree 2 at its centre, whatever the clause order
+    let star = SAT::from_array([[1, 2], [-1, 3], [-2, 4]]);
+    assert_eq!( DependencyGraph::form_sat(&star).max_d, 2 );
+
+    // only the pair sharing a variable is corrected
+    assert!( !sat.is_disjoint(0, 1) && sat.is_disjoint(2, 3) );
+    let mat = Match::from_pairs(vec![(0, 1), (2, 3)]);
+    let new = DependencyGraph::from_sat_with_match(&sat, &mat);
+    let pr = 1.0 / 16.0;
+    assert_eq!( new.p, vec![0.25 - pr * pr / 13.0, 0.125 - pr * pr / 13.0, 0.5, 0.0625] );
+
+    // no size cut-off: a chain of 400 overlapping 5-clauses keeps its p
+    let chain = SAT::from_vec( (0..400).map(|i| (1..=5).map(|j| 4 * i + j).collect()).collect() );
+    let dep = DependencyGraph::form_sat(&chain);
+    assert!( dep.p.iter().all(|&p| p == 1.0 / 32.0) );
+    let new = DependencyGraph::from_sat_with_match(&chain, &Match::from_pairs(vec![(0, 1), (398, 399)]));
+    assert!( new.p.iter().all(|&p| p > 0.0) );
+}
+
+#[test]
+fn matched_simulator_test() {
+    use crate::sat;
+    use crate::moser_tardos_algorithm::{self, AlgorithmSimulator, ExecuteResult, EnumResult};
+    use crate::random_space;
+
+    // the two clauses are matched, so every step resamples x1, x2 and x3
+    let sat = sat::SAT::from_array([[1, 2], [1, 3]]);
+    let result = moser_tardos_algorithm::enum_algorithm::<
+        moser_tardos_algorithm::MatchedAlgorithmSimulator<
+            random_space::LimitedRandomSpace
+        >
+    >(&sat, 1);
+
+    match result {
+        EnumResult::Failed => { panic!(); }
+        EnumResult::Success { p } => {
+            assert_eq!( p, vec![3.0 / 8.0, 9.0 / 64.0] );
+        }
+    }
+
+    let sat = sat::SAT::from_array([[-1, 2, 4], [1, 3, 5], [2, 4, 5], [-4, 5, 6]]);
+    let mut sand_box = 
+        moser_tardos_algorithm::MatchedAlgorithmSimulator::new
+            (sat, random_space::InfiniteRandomSpace::new(3));
+    sand_box.run_init();
+    assert!( matches!( sand_box.run_until_terminal(), ExecuteResult::Terminal { .. } ) );
+    assert!( sand_box.is_terminal() );
+}
+
+#[test]
+fn seeded_randomness_test() {
+    use crate::sat;
+    use crate::moser_tardos_algorithm::{self, BenchResult};
+    use crate::random_space::{self, InfiniteRandomSpace};
+
+    assert_ne!( random_space::derive_seed(7, 0), random_space::derive_seed(7, 1) );
+
+    let sat = sat::SAT::from_array([[-1, 2, 4], [1, 3, 5], [2, 4, 5], [-4, 5, 6]]);
+    let bench = |seed| match moser_tardos_algorithm::bench_algorithm::<
+        moser_tardos_algorithm::MTsAlgorithmSimulator<InfiniteRandomSpace>
+    >(&sat, 200, seed) {
+        BenchResult::Failed => { panic!(); }
+        BenchResult::Success { mean, sigma } => { (mean, sigma) }
+    };
+    assert_eq!( bench(3), bench(3) );
+}
+
+#[test]
+fn fetch_bits_test() {
+    use crate::random_space::{InfiniteRandomSpace, LimitedRandomSpace, RandomSpace};
+
+    // bulk fetches hand out exactly the bits of the single-bit stream
+    let mut bulk = InfiniteRandomSpace::new(11);
+    let mut single = InfiniteRandomSpace::new(11);
+    for n in [1, 3, 64, 0, 17, 60, 64, 5, 63, 2, 64, 64, 31, 33] {
+        let bits = bulk.fetch_bits(n).unwrap();
+        let expect = (0..n).fold(0u64, |acc, i| acc | (single.fetch_random_bit().unwrap() as u64) << i);
+        assert_eq!( bits, expect, "{n}" );
+    }
+
+    let ones = (0..100000).map(|_| bulk.fetch_bits(64).unwrap().count_ones() as u64).sum::<u64>();
+    assert!( (ones as f64 / 6400000.0 - 0.5).abs() < 0.001 );
+
+    let mut tape = LimitedRandomSpace::new(0b1011_0110, 8);
+    assert_eq!( tape.fetch_bits(3), Some(0b110) );
+    assert_eq!( tape.fetch_bits(5), Some(0b10110) );
+    assert_eq!( tape.fetch_bits(1), None );
+}
+
+#[test]
+fn bit_tape_test() {
+    use crate::sat;
+    use crate::moser_tardos_algorithm::{self, AlgorithmSimulator, ExecuteResult};
+    use crate::random_space::{LimitedRandomSpace, RandomSpace};
+
+    let str: String = (0..150).map(|i| if i % 3 == 0 || i % 7 == 0 { '1' } else { '0' }).collect();
+    let mut tape = LimitedRandomSpace::from_bit_string(&str);
+    assert_eq!( tape.size(), 150 );
+    assert_eq!( format!("{tape:?}"), str );
+    assert_eq!( format!("{:?}", LimitedRandomSpace::new(0b1101, 4)), "1011" );
+
+    let bits: Vec<bool> = (&mut tape).collect();
+    assert_eq!( bits.len(), 150 );
+    assert_eq!( tape.fetch_random_bit(), None );
+    assert_eq!( format!("{:?}", LimitedRandomSpace::from_bits(bits)), str );
+
+    // x1 = x2 = 1 violates the clause, the one resampling sets x1 = 0
+    let sat = sat::SAT::from_array([[1, 2]]);
+    let mut sand_box = moser_tardos_algorithm::MTsAlgorithmSimulator::new(
+        sat, LimitedRandomSpace::from_bit_string("11 01")
+    );
+    sand_box.run_init();
+    assert!( matches!( sand_box.run_until_terminal(), ExecuteResult::Terminal { step: 1 } ) );
+}
+
+#[test]
+fn recording_random_space_test() {
+    use crate::sat;
+    use crate::moser_tardos_algorithm::{self, AlgorithmSimulator, ExecuteResult};
+    use crate::random_space::{InfiniteRandomSpace, LimitedRandomSpace, RecordingRandomSpace, RandomSpace};
+
+    let mut recorder = RecordingRandomSpace::new(InfiniteRandomSpace::new(5));
+    let mut plain = InfiniteRandomSpace::new(5);
+    assert_eq!( recorder.fetch_bits(40), plain.fetch_bits(40) );
+    assert_eq!( recorder.fetch_random_bit(), plain.fetch_random_bit() );
+    assert_eq!( recorder.replay().size(), 41 );
+
+    // a recorded run replays step by step on the tape
+    let sat = sat::SAT::from_array([[-1, 2, 4], [1, 3, 5], [2, 4, 5], [-4, 5, 6], [1, 2, 3], [-2, -5, 6]]);
+    let (step, tape) = moser_tardos_algorithm::record_algorithm::<
+        moser_tardos_algorithm::MTsAlgorithmSimulator<RecordingRandomSpace<InfiniteRandomSpace>>
+    >(&sat, 9, 0).unwrap();
+
+    let mut sand_box = moser_tardos_algorithm::MTsAlgorithmSimulator::<LimitedRandomSpace>::new(sat, tape);
+    sand_box.run_init();
+    match sand_box.run_until_terminal() {
+        ExecuteResult::Terminal { step: replayed } => { assert_eq!( replayed, step ); }
+        _ => { panic!(); }
+    }
+    assert_eq!( sand_box.random_space().clone().next(), None );
+}
+
+#[test]
+fn resampling_table_test() {
+    use crate::sat;
+    use crate::moser_tardos_algorithm::{self, AlgorithmSimulator, ExecuteResult};
+    use crate::random_space::{InfiniteRandomSpace, LimitedRandomSpace, TableRandomSpace, RandomSpace};
+
+    let sat = sat::SAT::from_array([[-1, 2, 4], [1, 3, 5], [2, 4, 5], [-4, 5, 6], [1, 2, 3], [-2, -5, 6]]);
+    let m = sat.variable_count();
+
+    let mut source = InfiniteRandomSpace::new(3);
+    let column: Vec<Vec<bool>> = (0..m).map(|_| (0..200).map(|_| source.fetch_random_bit().unwrap()).collect()).collect();
+    let table = || TableRandomSpace::new(column.iter().map(|c| LimitedRandomSpace::from_bits(c.clone())).collect());
+
+    // the final value of every variable is the last entry it read from its column
+    let mut sand_box = moser_tardos_algorithm::MTsAlgorithmSimulator::new(sat.clone(), table());
+    sand_box.run_init();
+    assert!( matches!( sand_box.run_until_terminal(), ExecuteResult::Terminal { .. } ) );
+    for v in 1..=m {
+        let used = 200 - sand_box.random_space().column(v).clone().count();
+        assert!( used >= 1 );
+        assert_eq!( sand_box.get_varible()[v - 1], column[v - 1][used - 1] );
+    }
+
+    // the parallel rule runs on the very same table
+    let mut parallel = moser_tardos_algorithm::NewAlgorithmSimulator::new(sat.clone(), table());
+    parallel.run_init();
+    assert!( matches!( parallel.run_until_terminal(), ExecuteResult::Terminal { .. } ) );
+
+    let mut shared = TableRandomSpace::from_seed(m, 1);
+    assert_eq!( shared.fetch_random_bit(), None );
+    assert!( shared.fetch_variable_bits(&[1, 2, 6]).is_some() );
+}
+
+#[test]
+fn biased_variable_test() {
+    use crate::sat::SAT;
+    use crate::dep::DependencyGraph;
+    use crate::lll::{self, PredictedResult};
+    use crate::counting;
+    use crate::random_space::{InfiniteRandomSpace, RandomSpace};
+    use crate::moser_tardos_algorithm::{self, BenchResult};
+
+    let sat = SAT::from_array([[1, 2, 3], [-1, 2, 4], [-2, -3, 5], [4, -5, 6], [1, -6, 3]])
+        .with_bias(vec![0.8, 0.3, 0.5, 0.6, 0.1, 0.9]);
+    assert!( sat.is_biased() );
+
+    let dep = DependencyGraph::form_sat(&sat);
+    assert!( (dep.p[0] - 0.8 * 0.3 * 0.5).abs() < 1e-15 );
+    assert!( (dep.p[2] - 0.7 * 0.5 * 0.1).abs() < 1e-15 );
+    assert!( (sat.pr_land_pair(0, 1) - 0.0).abs() < 1e-15 );
+    assert!( (sat.pr_land_pair(0, 4) - 0.8 * 0.3 * 0.5 * 0.1).abs() < 1e-15 );
+
+    // exact density against enumeration of all assignments
+    let m = sat.variable_count();
+    let mut expect = 0.0;
+    for mask in 0..1usize << m {
+        let var: Vec<bool> = (0..m).map(|i| mask >> i & 1 == 1).collect();
+        if sat.check_violated(&var).iter().any(|&b| b) { continue; }
+        expect += (0..m).map(|i| if var[i] { sat.bias()[i] } else { 1.0 - sat.bias()[i] }).product::<f64>();
+    }
+    let density = counting::solution_density(&sat, 1000000).unwrap();
+    assert!( (density - expect).abs() < 1e-12 );
+    assert_eq!( lll::satisfiability_checker(&sat, 0), PredictedResult::UpperBound(1.0 / expect) );
+
+    match lll::estimated_satisfiability_checker(&sat, 20000, 1) {
+        PredictedResult::Estimate { lower, upper, .. } => { assert!( lower <= 1.0 / expect && 1.0 / expect <= upper ); }
+        _ => { panic!(); }
+    }
+
+    let mut r = InfiniteRandomSpace::new(2);
+    let ones = (0..100000).filter(|_| r.fetch_biased_bit(1, 0.3).unwrap()).count();
+    assert!( (ones as f64 / 100000.0 - 0.3).abs() < 0.01 );
+    assert_eq!( r.fetch_biased_bit(1, 0.0), Some(false) );
+    assert_eq!( r.fetch_biased_bit(1, 1.0), Some(true) );
+
+    assert!( matches!( moser_tardos_algorithm::bench_algorithm::<
+        moser_tardos_algorithm::MTsAlgorithmSimulator<InfiniteRandomSpace>
+    >(&sat, 100, 4), BenchResult::Success { .. } ) );
+}
+
+#[test]
+fn finite_domain_test() {
+    use crate::sat::SAT;
+    use crate::dep::DependencyGraph;
+    use crate::domain::{FiniteEvent, FiniteInstance};
+    use crate::event::Instance;
+    use crate::lll::{self, PredictedResult};
+    use crate::moser_tardos_algorithm::{AlgorithmSimulator, ExecuteResult, MTsAlgorithmSimulator, NewAlgorithmSimulator};
+    use crate::random_space::{InfiniteRandomSpace, RandomSpace};
+
+    // a CNF as boolean events keeps its probabilities and dependencies
+    let sat = SAT::from_array([[1, 2, 3], [-1, 2, 4], [-2, -3, 5], [4, -5, 6], [1, -6, 3]])
+        .with_bias(vec![0.8, 0.3, 0.5, 0.6, 0.1, 0.9]);
+    let instance = FiniteInstance::from_sat(&sat);
+    let (a, b) = (DependencyGraph::form_sat(&sat), instance.dependency_graph());
+    for id in 0..sat.size() {
+        assert!( (a.p[id] - b.p[id]).abs() < 1e-15 );
+        assert_eq!( a.get_gamma(id).len(), b.get_gamma(id).len() );
+    }
+
+    // 4-colouring a 5-cycle, a bad event is an edge with equal colours
+    let domain = [4; 5];
+    let event = (0..5).map(|u| FiniteEvent::from_predicate(vec![u, (u + 1) % 5], &domain, |t| t[0] == t[1])).collect();
+    let instance = FiniteInstance::new(FiniteInstance::uniform(5, 4), event);
+    assert!( (instance.probability(0) - 0.25).abs() < 1e-15 );
+    assert_eq!( instance.dependency_graph().max_d, 2 );
+
+    // the chromatic polynomial of C5 gives 3^5 - 3 proper colourings
+    match lll::satisfiability_checker(&instance, 0) {
+        PredictedResult::UpperBound(x) => { assert!( (x - 1024.0 / 240.0).abs() < 1e-9 ); }
+        other => { panic!("{other}"); }
+    }
+    match lll::estimated_satisfiability_checker(&instance, 20000, 3) {
+        PredictedResult::Estimate { lower, upper, .. } => { assert!( lower < 1024.0 / 240.0 && 1024.0 / 240.0 < upper ); }
+        other => { panic!("{other}"); }
+    }
+
+    let mut sand_box = MTsAlgorithmSimulator::new(instance.clone(), InfiniteRandomSpace::new(5));
+    sand_box.run_init();
+    assert!( matches!( sand_box.run_until_terminal(), ExecuteResult::Terminal { .. } ) );
+    assert!( instance.check_violated(sand_box.get_varible()).iter().all(|&b| !b) );
+
+    let mut sand_box: NewAlgorithmSimulator<_, FiniteInstance> = AlgorithmSimulator::new(instance.clone(), InfiniteRandomSpace::new(5));
+    sand_box.run_init();
+    assert!( matches!( sand_box.run_until_terminal(), ExecuteResult::Terminal { .. } ) );
+    assert!( sand_box.is_terminal() );
+
+    let mut r = InfiniteRandomSpace::new(8);
+    let mut cnt = [0; 3];
+    for _ in 0..60000 { cnt[r.fetch_value(1, &[0.2, 0.5, 0.3]).unwrap()] += 1; }
+    for (c, p) in cnt.iter().zip([0.2, 0.5, 0.3]) {
+        assert!( (*c as f64 / 60000.0 - p).abs() < 0.01 );
+    }
+}
+
+#[test]
+fn hypergraph_test() {
+    use crate::hypergraph::Hypergraph;
+    use crate::event::{BadEvent, Instance};
+    use crate::lll::{self, PredictedResult, InvalidReason};
+    use crate::moser_tardos_algorithm::{AlgorithmSimulator, ExecuteResult, MTsAlgorithmSimulator};
+    use crate::random_space::InfiniteRandomSpace;
+
+    // the Fano plane has no proper 2-colouring
+    let fano = Hypergraph::new(7, vec![
+        vec![0, 1, 2], vec![0, 3, 4], vec![0, 5, 6], vec![1, 3, 5], vec![1, 4, 6], vec![2, 3, 6], vec![2, 4, 5]
+    ]);
+    assert!( (fano.probability(0) - 0.25).abs() < 1e-15 );
+    assert_eq!( fano.dependency_graph().max_d, 6 );
+    assert_eq!( lll::satisfiability_checker(&fano, 0), PredictedResult::Invalid(InvalidReason::Unsatisfiable) );
+
+    let g = Hypergraph::random_uniform(10, 8, 4, 3);
+    assert_eq!( g.size(), 8 );
+    assert!( g.events().iter().all(|e| e.variables().len() == 4) );
+
+    let path = std::env::temp_dir().join("hypergraph_test.hg");
+    g.to_file(path.to_str().unwrap());
+    let h = Hypergraph::from_file(path.to_str().unwrap());
+    assert_eq!( g.events().iter().map(|e| e.variables()).collect::<Vec<_>>(), h.events().iter().map(|e| e.variables()).collect::<Vec<_>>() );
+    assert_eq!( h.variable_count(), 10 );
+
+    // the density through the CNF against every colouring
+    let g = g.with_bias(vec![0.3; 10]);
+    assert!( (g.probability(0) - (0.3f64.powi(4) + 0.7f64.powi(4))).abs() < 1e-15 );
+    let exact: f64 = (0..1 << 10).map( |mask: usize| {
+        let value: Vec<bool> = (0..10).map(|v| (mask >> v) & 1 == 1).collect();
+        if g.check_violated(&value).iter().any(|&b| b) { 0.0 }
+        else { value.iter().map(|&b| if b { 0.3 } else { 0.7 }).product::<f64>() }
+    } ).sum();
+    assert!( (g.log2_density().unwrap() - exact.log2()).abs() < 1e-9 );
+
+    let mut sand_box = MTsAlgorithmSimulator::new(g.clone(), InfiniteRandomSpace::new(4));
+    sand_box.run_init();
+    assert!( matches!( sand_box.run_until_terminal(), ExecuteResult::Terminal { .. } ) );
+    assert!( g.check_violated(sand_box.get_varible()).iter().all(|&b| !b) );
+}
+
+#[test]
+fn graph_colouring_test() {
+    use crate::graph::Graph;
+    use crate::event::Instance;
+    use crate::moser_tardos_algorithm::{AlgorithmSimulator, ExecuteResult, MTsAlgorithmSimulator};
+    use crate::random_space::InfiniteRandomSpace;
+
+    let k4 = Graph::new(4, vec![(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+    assert_eq!( k4.even_cycles(4).len(), 3 );
+    assert_eq!( k4.even_cycles(6).len(), 3 );
+    assert!( k4.even_cycles(6).iter().all(|c| c.len() == 4) );
+
+    let dir = std::env::temp_dir();
+    let path = dir.join("graph_colouring_test.col");
+    let g = Graph::random_gnm(9, 12, 2);
+    g.to_dimacs(path.to_str().unwrap());
+    assert_eq!( format!("{:?}", Graph::from_file(path.to_str().unwrap())), format!("{g:?}") );
+
+    let path = dir.join("graph_colouring_test.edges");
+    std::fs::write(&path, "# a 5-cycle\n0 1\n1 2\n2 3\n3 4\n4 0\n").unwrap();
+    let c5 = Graph::from_file(path.to_str().unwrap());
+
+    // (q - 1)^5 - (q - 1) proper 3-colourings of C5
+    let instance = c5.vertex_colouring(3);
+    assert!( (instance.probability(0) - 1.0 / 3.0).abs() < 1e-15 );
+    assert!( (instance.log2_density().unwrap() - (30.0f64 / 243.0).log2()).abs() < 1e-9 );
+
+    // of the 18 proper 3-edge-colourings of C4, the 6 alternating ones
+    // have a bichromatic cycle
+    let c4 = Graph::new(4, vec![(0, 1), (1, 2), (2, 3), (3, 0)]);
+    let instance = c4.acyclic_edge_colouring(3);
+    assert_eq!( instance.size(), 5 );
+    assert!( (instance.probability(4) - 6.0 / 81.0).abs() < 1e-15 );
+    assert!( (instance.log2_density().unwrap() - (12.0f64 / 81.0).log2()).abs() < 1e-9 );
+
+    // a 6-cycle alternating two colours is only bad without the relaxation
+    let c6 = Graph::new(6, (0..6).map(|u| (u, (u + 1) % 6)).collect());
+    // edges sorted as 01, 05, 12, 23, 34, 45
+    let alternating = vec![0, 1, 1, 0, 1, 0];
+    assert!( c6.acyclic_edge_colouring(3).check_violated(&alternating).iter().any(|&b| b) );
+    assert!( c6.acyclic_edge_colouring_up_to(3, 4).check_violated(&alternating).iter().all(|&b| !b) );
+
+    let instance = g.acyclic_edge_colouring(8);
+    let mut sand_box = MTsAlgorithmSimulator::new(instance.clone(), InfiniteRandomSpace::new(6));
+    sand_box.run_init();
+    assert!( matches!( sand_box.run_until_terminal(), ExecuteResult::Terminal { .. } ) );
+    assert!( instance.check_violated(sand_box.get_varible()).iter().all(|&b| !b) );
+}